@@ -1,8 +1,10 @@
 use nih_plug::prelude::*;
+use nih_plug::wrapper::state::ParamValue;
 use nih_plug_egui::{create_egui_editor, egui, EguiState};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
 
 // ========== PRESET SYSTEM ==========
 #[derive(Clone, Copy, PartialEq)]
@@ -25,6 +27,19 @@ struct PresetData {
     attack: f32, decay: f32, sustain: f32, release: f32,
     lfo_rate: f32, lfo_depth: f32, lfo_wave: i32, lfo_target: i32, porta: f32,
     delay_mix: f32, delay_time: f32, delay_fb: f32, reverb_mix: f32, reverb_size: f32,
+    filter_model: i32, accent: f32, filter_key: f32, filter_vel: f32, key_center: i32,
+    filter2_type: i32, filter2_cut: f32, filter2_res: f32, filter_routing: i32,
+    vowel: f32, vowel_env: f32,
+    atk_curve: f32, dec_curve: f32, rel_curve: f32, env_mode: i32,
+    pitch_env: f32, pitch_decay: f32,
+    vel_amp: f32, vel_curve: i32, retrigger: i32,
+    env_delay: f32, env_hold: f32,
+    lfo_sync: i32, lfo_div: i32, lfo_mode: i32, lfo_phase: f32, lfo_fade: f32,
+    lfo2: LfoPreset, lfo3: LfoPreset, lfo_shapes: [LfoShape; 3],
+    mod_slots: [ModSlot; MOD_SLOTS],
+    bend_up: i32, bend_down: i32,
+    macros: [MacroPreset; MACROS],
+    glide_type: i32, glide_legato: i32,
 }
 
 impl Default for PresetData {
@@ -38,49 +53,88 @@ impl Default for PresetData {
             attack: 0.005, decay: 0.15, sustain: 0.7, release: 0.15,
             lfo_rate: 2.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007,
             delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.4, reverb_mix: 0.0, reverb_size: 0.5,
+            filter_model: 0, accent: 0.0, filter_key: 0.0, filter_vel: 0.0, key_center: 60,
+            filter2_type: 0, filter2_cut: 2000.0, filter2_res: 0.2, filter_routing: 0,
+            vowel: 0.0, vowel_env: 0.0,
+            atk_curve: 0.0, dec_curve: 0.0, rel_curve: 0.0, env_mode: 0,
+            pitch_env: 0.0, pitch_decay: 0.1,
+            vel_amp: 1.0, vel_curve: 0, retrigger: 0,
+            env_delay: 0.0, env_hold: 0.0,
+            lfo_sync: 0, lfo_div: 7, lfo_mode: 0, lfo_phase: 0.0, lfo_fade: 0.0,
+            lfo2: LfoPreset::default(), lfo3: LfoPreset::default(), lfo_shapes: [LfoShape::default(); 3],
+            mod_slots: default_mod_slots(),
+            bend_up: 2, bend_down: 2,
+            macros: default_macros(),
+            glide_type: 0, glide_legato: 0,
         }
     }
 }
 
+impl PresetData {
+    // LFO 1 as an LfoPreset; it keeps its original flat fields for the factory presets
+    fn lfo1(&self) -> LfoPreset {
+        LfoPreset {
+            rate: self.lfo_rate, depth: self.lfo_depth, wave: self.lfo_wave, target: self.lfo_target,
+            sync: self.lfo_sync, div: self.lfo_div, mode: self.lfo_mode, phase: self.lfo_phase, fade: self.lfo_fade,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct LfoPreset { rate: f32, depth: f32, wave: i32, target: i32, sync: i32, div: i32, mode: i32, phase: f32, fade: f32 }
+
+impl Default for LfoPreset {
+    fn default() -> Self { Self { rate: 2.0, depth: 0.0, wave: 0, target: 1, sync: 0, div: 7, mode: 0, phase: 0.0, fade: 0.0 } }
+}
+
 fn create_factory_presets() -> Vec<PresetData> {
+    // Gated 1/16 stutter with a curved dip in the second half
+    let stutter = LfoShape::from_points(&[
+        (0.0, 1.0, 0.0), (0.1875, 1.0, 0.0), (0.1875, -1.0, 0.0), (0.25, 0.5, 0.0), (0.4375, 0.5, 0.0), (0.4375, -1.0, 0.0),
+        (0.5, 1.0, 0.0), (0.75, -1.0, 0.6), (0.875, 0.2, 0.0), (0.875, -1.0, 0.0),
+    ]);
     vec![
         PresetData::default(),
         // Sub (5)
-        PresetData { name: "Deep Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.0, sub_vol: 1.0, unison: 1, spread: 0.0, filter_cut: 150.0, filter_res: 0.2, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 2, low_boost: 0.8, attack: 0.005, decay: 0.1, sustain: 0.9, release: 0.2, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "808 Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.0, sub_vol: 0.8, unison: 1, spread: 0.0, filter_cut: 200.0, filter_res: 0.3, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 2, low_boost: 0.9, attack: 0.001, decay: 0.8, sustain: 0.0, release: 0.5, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0052, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "Sine Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.0, sub_vol: 0.9, unison: 1, spread: 0.0, filter_cut: 120.0, filter_res: 0.1, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 0, low_boost: 1.0, attack: 0.01, decay: 0.1, sustain: 1.0, release: 0.2, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "Dark Sub".into(), category: PresetCategory::Sub, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.2, sub_vol: 0.85, unison: 1, spread: 0.0, filter_cut: 180.0, filter_res: 0.4, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.7, attack: 0.005, decay: 0.2, sustain: 0.8, release: 0.25, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "Rumble Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 1, osc2_det: -5.0, osc2_mix: 0.15, sub_vol: 0.9, unison: 2, spread: 0.1, filter_cut: 160.0, filter_res: 0.35, filter_env: 0.03, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 2, low_boost: 0.85, attack: 0.01, decay: 0.15, sustain: 0.85, release: 0.3, lfo_rate: 0.3, lfo_depth: 0.1, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
+        PresetData { name: "Deep Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.0, sub_vol: 1.0, unison: 1, spread: 0.0, filter_cut: 150.0, filter_res: 0.2, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 2, low_boost: 0.8, attack: 0.005, decay: 0.1, sustain: 0.9, release: 0.2, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
+        PresetData { name: "808 Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.0, sub_vol: 0.8, unison: 1, spread: 0.0, filter_cut: 200.0, filter_res: 0.3, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 2, low_boost: 0.9, attack: 0.001, decay: 0.8, sustain: 0.0, release: 0.5, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0052, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, pitch_env: 12.0, pitch_decay: 0.08, env_hold: 0.1, ..PresetData::default() },
+        PresetData { name: "Sine Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.0, sub_vol: 0.9, unison: 1, spread: 0.0, filter_cut: 120.0, filter_res: 0.1, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 0, low_boost: 1.0, attack: 0.01, decay: 0.1, sustain: 1.0, release: 0.2, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
+        PresetData { name: "Dark Sub".into(), category: PresetCategory::Sub, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.2, sub_vol: 0.85, unison: 1, spread: 0.0, filter_cut: 180.0, filter_res: 0.4, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.7, attack: 0.005, decay: 0.2, sustain: 0.8, release: 0.25, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
+        PresetData { name: "Rumble Sub".into(), category: PresetCategory::Sub, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 1, osc2_det: -5.0, osc2_mix: 0.15, sub_vol: 0.9, unison: 2, spread: 0.1, filter_cut: 160.0, filter_res: 0.35, filter_env: 0.03, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 2, low_boost: 0.85, attack: 0.01, decay: 0.15, sustain: 0.85, release: 0.3, lfo_rate: 0.3, lfo_depth: 0.1, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
         // Fat (6)
-        PresetData { name: "Fat Saw".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -5.0, osc2_wave: 1, osc2_det: 5.0, osc2_mix: 0.5, sub_vol: 0.6, unison: 6, spread: 0.3, filter_cut: 500.0, filter_res: 0.5, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.6, attack: 0.005, decay: 0.2, sustain: 0.7, release: 0.15, lfo_rate: 0.5, lfo_depth: 0.05, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "Massive".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -10.0, osc2_wave: 2, osc2_det: 10.0, osc2_mix: 0.6, sub_vol: 0.7, unison: 8, spread: 0.4, filter_cut: 600.0, filter_res: 0.45, filter_env: 0.08, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 1, low_boost: 0.7, attack: 0.01, decay: 0.15, sustain: 0.75, release: 0.2, lfo_rate: 0.3, lfo_depth: 0.08, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.05, delay_time: 0.25, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.4 },
-        PresetData { name: "Wall of Bass".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -15.0, osc2_wave: 1, osc2_det: 15.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 8, spread: 0.5, filter_cut: 800.0, filter_res: 0.4, filter_env: 0.07, filter_type: 0, filter_slope: 1, drive: 0.18, drive_type: 2, low_boost: 0.5, attack: 0.02, decay: 0.2, sustain: 0.8, release: 0.25, lfo_rate: 0.2, lfo_depth: 0.1, lfo_wave: 0, lfo_target: 1, porta: 0.0052, delay_mix: 0.1, delay_time: 0.3, delay_fb: 0.35, reverb_mix: 0.1, reverb_size: 0.5 },
-        PresetData { name: "Thick Square".into(), category: PresetCategory::Fat, osc1_wave: 2, osc1_det: -7.0, osc2_wave: 2, osc2_det: 7.0, osc2_mix: 0.5, sub_vol: 0.55, unison: 5, spread: 0.25, filter_cut: 450.0, filter_res: 0.55, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 2, low_boost: 0.55, attack: 0.005, decay: 0.18, sustain: 0.65, release: 0.15, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "Reese Monster".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -20.0, osc2_wave: 1, osc2_det: 20.0, osc2_mix: 0.5, sub_vol: 0.4, unison: 4, spread: 0.35, filter_cut: 700.0, filter_res: 0.35, filter_env: 0.07, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 2, low_boost: 0.45, attack: 0.01, decay: 0.2, sustain: 0.75, release: 0.2, lfo_rate: 0.15, lfo_depth: 0.15, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.4 },
-        PresetData { name: "Phat Mono".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.4, sub_vol: 0.65, unison: 4, spread: 0.2, filter_cut: 550.0, filter_res: 0.5, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.6, attack: 0.005, decay: 0.15, sustain: 0.6, release: 0.12, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3 },
-        // Acid (5)
-        PresetData { name: "303 Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.3, sub_vol: 0.3, unison: 1, spread: 0.0, filter_cut: 400.0, filter_res: 0.85, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.4, attack: 0.001, decay: 0.15, sustain: 0.0, release: 0.1, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.15, delay_time: 0.2, delay_fb: 0.4, reverb_mix: 0.05, reverb_size: 0.3 },
-        PresetData { name: "Squelch".into(), category: PresetCategory::Acid, osc1_wave: 2, osc1_det: 0.0, osc2_wave: 1, osc2_det: 0.0, osc2_mix: 0.2, sub_vol: 0.35, unison: 1, spread: 0.0, filter_cut: 350.0, filter_res: 0.9, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.35, attack: 0.001, decay: 0.12, sustain: 0.0, release: 0.08, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.1, delay_time: 0.18, delay_fb: 0.35, reverb_mix: 0.0, reverb_size: 0.3 },
-        PresetData { name: "Resonant Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 1, osc2_det: 5.0, osc2_mix: 0.25, sub_vol: 0.4, unison: 2, spread: 0.1, filter_cut: 450.0, filter_res: 0.92, filter_env: 0.0258, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.45, attack: 0.001, decay: 0.18, sustain: 0.1, release: 0.12, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0052, delay_mix: 0.2, delay_time: 0.22, delay_fb: 0.45, reverb_mix: 0.08, reverb_size: 0.35 },
-        PresetData { name: "Dirty Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.4, sub_vol: 0.3, unison: 1, spread: 0.0, filter_cut: 380.0, filter_res: 0.88, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 1, low_boost: 0.4, attack: 0.001, decay: 0.14, sustain: 0.0, release: 0.1, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.008, delay_mix: 0.12, delay_time: 0.2, delay_fb: 0.38, reverb_mix: 0.03, reverb_size: 0.3 },
-        PresetData { name: "Acid Stab".into(), category: PresetCategory::Acid, osc1_wave: 2, osc1_det: 0.0, osc2_wave: 2, osc2_det: 7.0, osc2_mix: 0.35, sub_vol: 0.25, unison: 2, spread: 0.15, filter_cut: 500.0, filter_res: 0.8, filter_env: 0.0257, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.35, attack: 0.001, decay: 0.1, sustain: 0.0, release: 0.08, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.18, delay_time: 0.15, delay_fb: 0.5, reverb_mix: 0.1, reverb_size: 0.4 },
-        // Wobble (5)
-        PresetData { name: "Dubstep Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 1, osc2_det: -7.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 4, spread: 0.2, filter_cut: 800.0, filter_res: 0.7, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.5, attack: 0.01, decay: 0.1, sustain: 0.8, release: 0.15, lfo_rate: 4.0, lfo_depth: 0.8, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.1, reverb_size: 0.4 },
-        PresetData { name: "Slow Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: -5.0, osc2_wave: 2, osc2_det: 5.0, osc2_mix: 0.45, sub_vol: 0.55, unison: 3, spread: 0.25, filter_cut: 700.0, filter_res: 0.65, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 2, low_boost: 0.55, attack: 0.02, decay: 0.15, sustain: 0.75, release: 0.2, lfo_rate: 1.5, lfo_depth: 0.75, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.35, delay_fb: 0.35, reverb_mix: 0.15, reverb_size: 0.5 },
-        PresetData { name: "Fast Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 1, osc2_det: 0.0, osc2_mix: 0.4, sub_vol: 0.45, unison: 4, spread: 0.15, filter_cut: 900.0, filter_res: 0.75, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.18, drive_type: 2, low_boost: 0.45, attack: 0.005, decay: 0.1, sustain: 0.85, release: 0.12, lfo_rate: 12.0, lfo_depth: 0.7, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.08, reverb_size: 0.35 },
-        PresetData { name: "Square Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 2, osc1_det: 0.0, osc2_wave: 1, osc2_det: 5.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 3, spread: 0.2, filter_cut: 750.0, filter_res: 0.68, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.5, attack: 0.01, decay: 0.12, sustain: 0.8, release: 0.15, lfo_rate: 6.0, lfo_depth: 0.85, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.28, delay_fb: 0.32, reverb_mix: 0.1, reverb_size: 0.4 },
-        PresetData { name: "Morphing Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: -10.0, osc2_wave: 2, osc2_det: 10.0, osc2_mix: 0.5, sub_vol: 0.4, unison: 5, spread: 0.3, filter_cut: 850.0, filter_res: 0.72, filter_env: 0.03, filter_type: 0, filter_slope: 1, drive: 0.16, drive_type: 2, low_boost: 0.48, attack: 0.015, decay: 0.12, sustain: 0.78, release: 0.18, lfo_rate: 3.0, lfo_depth: 0.82, lfo_wave: 3, lfo_target: 1, porta: 0.0, delay_mix: 0.08, delay_time: 0.32, delay_fb: 0.38, reverb_mix: 0.12, reverb_size: 0.45 },
-        // Growl (5)
-        PresetData { name: "Growl".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -15.0, osc2_wave: 2, osc2_det: 15.0, osc2_mix: 0.6, sub_vol: 0.35, unison: 6, spread: 0.4, filter_cut: 700.0, filter_res: 0.75, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.155, drive_type: 1, low_boost: 0.4, attack: 0.01, decay: 0.1, sustain: 0.8, release: 0.15, lfo_rate: 8.0, lfo_depth: 0.6, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.35 },
-        PresetData { name: "Aggressive".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -20.0, osc2_wave: 1, osc2_det: 20.0, osc2_mix: 0.55, sub_vol: 0.3, unison: 7, spread: 0.45, filter_cut: 800.0, filter_res: 0.8, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 1, low_boost: 0.35, attack: 0.005, decay: 0.08, sustain: 0.85, release: 0.12, lfo_rate: 10.0, lfo_depth: 0.65, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.03, reverb_size: 0.3 },
-        PresetData { name: "Screamer".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -25.0, osc2_wave: 2, osc2_det: 25.0, osc2_mix: 0.65, sub_vol: 0.25, unison: 8, spread: 0.5, filter_cut: 1000.0, filter_res: 0.85, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 3, low_boost: 0.3, attack: 0.003, decay: 0.1, sustain: 0.9, release: 0.1, lfo_rate: 12.0, lfo_depth: 0.7, lfo_wave: 1, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.2, delay_fb: 0.35, reverb_mix: 0.05, reverb_size: 0.35 },
-        PresetData { name: "Metallic".into(), category: PresetCategory::Growl, osc1_wave: 2, osc1_det: -30.0, osc2_wave: 2, osc2_det: 30.0, osc2_mix: 0.5, sub_vol: 0.2, unison: 6, spread: 0.35, filter_cut: 1200.0, filter_res: 0.7, filter_env: 0.03, filter_type: 0, filter_slope: 1, drive: 0.158, drive_type: 1, low_boost: 0.25, attack: 0.001, decay: 0.15, sustain: 0.7, release: 0.15, lfo_rate: 15.0, lfo_depth: 0.5, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.1, delay_time: 0.15, delay_fb: 0.4, reverb_mix: 0.08, reverb_size: 0.4 },
-        PresetData { name: "Chaos".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -35.0, osc2_wave: 2, osc2_det: 35.0, osc2_mix: 0.6, sub_vol: 0.2, unison: 8, spread: 0.5, filter_cut: 900.0, filter_res: 0.82, filter_env: 0.07, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 3, low_boost: 0.25, attack: 0.002, decay: 0.12, sustain: 0.88, release: 0.1, lfo_rate: 8.0, lfo_depth: 0.9, lfo_wave: 1, lfo_target: 1, porta: 0.0, delay_mix: 0.08, delay_time: 0.18, delay_fb: 0.45, reverb_mix: 0.1, reverb_size: 0.45 },
-        // Clean (4)
-        PresetData { name: "Clean Finger".into(), category: PresetCategory::Clean, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 3, osc2_det: 0.0, osc2_mix: 0.3, sub_vol: 0.4, unison: 1, spread: 0.0, filter_cut: 1500.0, filter_res: 0.2, filter_env: 0.025, filter_type: 0, filter_slope: 0, drive: 0.0, drive_type: 0, low_boost: 0.3, attack: 0.005, decay: 0.3, sustain: 0.5, release: 0.3, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.1, delay_time: 0.35, delay_fb: 0.3, reverb_mix: 0.15, reverb_size: 0.5 },
-        PresetData { name: "Soft Synth".into(), category: PresetCategory::Clean, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 5.0, osc2_mix: 0.4, sub_vol: 0.5, unison: 2, spread: 0.1, filter_cut: 1200.0, filter_res: 0.25, filter_env: 0.03, filter_type: 0, filter_slope: 0, drive: 0.05, drive_type: 0, low_boost: 0.4, attack: 0.02, decay: 0.2, sustain: 0.6, release: 0.35, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.12, delay_time: 0.4, delay_fb: 0.35, reverb_mix: 0.2, reverb_size: 0.55 },
-        PresetData { name: "Mellow".into(), category: PresetCategory::Clean, osc1_wave: 3, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.35, sub_vol: 0.45, unison: 1, spread: 0.0, filter_cut: 800.0, filter_res: 0.15, filter_env: 0.025, filter_type: 0, filter_slope: 0, drive: 0.0, drive_type: 0, low_boost: 0.35, attack: 0.03, decay: 0.25, sustain: 0.55, release: 0.4, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.08, delay_time: 0.38, delay_fb: 0.28, reverb_mix: 0.25, reverb_size: 0.6 },
-        PresetData { name: "Warm DI".into(), category: PresetCategory::Clean, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.2, sub_vol: 0.55, unison: 1, spread: 0.0, filter_cut: 1000.0, filter_res: 0.2, filter_env: 0.07, filter_type: 0, filter_slope: 0, drive: 0.1, drive_type: 2, low_boost: 0.5, attack: 0.01, decay: 0.2, sustain: 0.65, release: 0.25, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.3, delay_fb: 0.25, reverb_mix: 0.1, reverb_size: 0.45 },
+        PresetData { name: "Fat Saw".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -5.0, osc2_wave: 1, osc2_det: 5.0, osc2_mix: 0.5, sub_vol: 0.6, unison: 6, spread: 0.3, filter_cut: 500.0, filter_res: 0.5, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.6, attack: 0.005, decay: 0.2, sustain: 0.7, release: 0.15, lfo_rate: 0.5, lfo_depth: 0.05, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
+        PresetData { name: "Massive".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -10.0, osc2_wave: 2, osc2_det: 10.0, osc2_mix: 0.6, sub_vol: 0.7, unison: 8, spread: 0.4, filter_cut: 600.0, filter_res: 0.45, filter_env: 0.08, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 1, low_boost: 0.7, attack: 0.01, decay: 0.15, sustain: 0.75, release: 0.2, lfo_rate: 0.3, lfo_depth: 0.08, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.05, delay_time: 0.25, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.4, ..PresetData::default() },
+        PresetData { name: "Wall of Bass".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -15.0, osc2_wave: 1, osc2_det: 15.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 8, spread: 0.5, filter_cut: 800.0, filter_res: 0.4, filter_env: 0.07, filter_type: 0, filter_slope: 1, drive: 0.18, drive_type: 2, low_boost: 0.5, attack: 0.02, decay: 0.2, sustain: 0.8, release: 0.25, lfo_rate: 0.2, lfo_depth: 0.1, lfo_wave: 0, lfo_target: 1, porta: 0.0052, delay_mix: 0.1, delay_time: 0.3, delay_fb: 0.35, reverb_mix: 0.1, reverb_size: 0.5, ..PresetData::default() },
+        PresetData { name: "Thick Square".into(), category: PresetCategory::Fat, osc1_wave: 2, osc1_det: -7.0, osc2_wave: 2, osc2_det: 7.0, osc2_mix: 0.5, sub_vol: 0.55, unison: 5, spread: 0.25, filter_cut: 450.0, filter_res: 0.55, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 2, low_boost: 0.55, attack: 0.005, decay: 0.18, sustain: 0.65, release: 0.15, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
+        PresetData { name: "Reese Monster".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: -20.0, osc2_wave: 1, osc2_det: 20.0, osc2_mix: 0.5, sub_vol: 0.4, unison: 4, spread: 0.35, filter_cut: 700.0, filter_res: 0.35, filter_env: 0.07, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 2, low_boost: 0.45, attack: 0.01, decay: 0.2, sustain: 0.75, release: 0.2, lfo_rate: 0.15, lfo_depth: 0.15, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.4, ..PresetData::default() },
+        PresetData { name: "Phat Mono".into(), category: PresetCategory::Fat, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.4, sub_vol: 0.65, unison: 4, spread: 0.2, filter_cut: 550.0, filter_res: 0.5, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.6, attack: 0.005, decay: 0.15, sustain: 0.6, release: 0.12, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.0, reverb_size: 0.3, ..PresetData::default() },
+        // Acid (6)
+        PresetData { name: "303 Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.3, sub_vol: 0.3, unison: 1, spread: 0.0, filter_cut: 400.0, filter_res: 0.85, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.4, attack: 0.001, decay: 0.15, sustain: 0.0, release: 0.1, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.15, delay_time: 0.2, delay_fb: 0.4, reverb_mix: 0.05, reverb_size: 0.3, filter_model: 2, accent: 0.5, ..PresetData::default() },
+        PresetData { name: "Glide Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.3, sub_vol: 0.3, unison: 1, spread: 0.0, filter_cut: 350.0, filter_res: 0.8, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.4, attack: 0.001, decay: 0.15, sustain: 0.0, release: 0.1, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.06, delay_mix: 0.15, delay_time: 0.2, delay_fb: 0.4, reverb_mix: 0.05, reverb_size: 0.3, filter_model: 2, accent: 0.5, retrigger: 2, glide_type: 1, glide_legato: 1, ..PresetData::default() },
+        PresetData { name: "Squelch".into(), category: PresetCategory::Acid, osc1_wave: 2, osc1_det: 0.0, osc2_wave: 1, osc2_det: 0.0, osc2_mix: 0.2, sub_vol: 0.35, unison: 1, spread: 0.0, filter_cut: 350.0, filter_res: 0.9, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.35, attack: 0.001, decay: 0.12, sustain: 0.0, release: 0.08, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.1, delay_time: 0.18, delay_fb: 0.35, reverb_mix: 0.0, reverb_size: 0.3, filter_model: 2, accent: 0.6, ..PresetData::default() },
+        PresetData { name: "Resonant Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 1, osc2_det: 5.0, osc2_mix: 0.25, sub_vol: 0.4, unison: 2, spread: 0.1, filter_cut: 450.0, filter_res: 0.92, filter_env: 0.0258, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.45, attack: 0.001, decay: 0.18, sustain: 0.1, release: 0.12, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0052, delay_mix: 0.2, delay_time: 0.22, delay_fb: 0.45, reverb_mix: 0.08, reverb_size: 0.35, ..PresetData::default() },
+        PresetData { name: "Dirty Acid".into(), category: PresetCategory::Acid, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 0.0, osc2_mix: 0.4, sub_vol: 0.3, unison: 1, spread: 0.0, filter_cut: 380.0, filter_res: 0.88, filter_env: 0.0252, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 1, low_boost: 0.4, attack: 0.001, decay: 0.14, sustain: 0.0, release: 0.1, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.008, delay_mix: 0.12, delay_time: 0.2, delay_fb: 0.38, reverb_mix: 0.03, reverb_size: 0.3, filter_model: 2, accent: 0.5, ..PresetData::default() },
+        PresetData { name: "Acid Stab".into(), category: PresetCategory::Acid, osc1_wave: 2, osc1_det: 0.0, osc2_wave: 2, osc2_det: 7.0, osc2_mix: 0.35, sub_vol: 0.25, unison: 2, spread: 0.15, filter_cut: 500.0, filter_res: 0.8, filter_env: 0.0257, filter_type: 0, filter_slope: 1, drive: 0.05, drive_type: 3, low_boost: 0.35, attack: 0.001, decay: 0.1, sustain: 0.0, release: 0.08, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.18, delay_time: 0.15, delay_fb: 0.5, reverb_mix: 0.1, reverb_size: 0.4, ..PresetData::default() },
+        // Wobble (6)
+        PresetData { name: "Dubstep Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 1, osc2_det: -7.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 4, spread: 0.2, filter_cut: 800.0, filter_res: 0.7, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.5, attack: 0.01, decay: 0.1, sustain: 0.8, release: 0.15, lfo_rate: 4.0, lfo_depth: 0.8, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.1, reverb_size: 0.4, lfo_sync: 1, lfo_div: 7, ..PresetData::default() },
+        PresetData { name: "Slow Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: -5.0, osc2_wave: 2, osc2_det: 5.0, osc2_mix: 0.45, sub_vol: 0.55, unison: 3, spread: 0.25, filter_cut: 700.0, filter_res: 0.65, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 2, low_boost: 0.55, attack: 0.02, decay: 0.15, sustain: 0.75, release: 0.2, lfo_rate: 1.5, lfo_depth: 0.75, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.35, delay_fb: 0.35, reverb_mix: 0.15, reverb_size: 0.5, lfo_sync: 1, lfo_div: 4, ..PresetData::default() },
+        PresetData { name: "Fast Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 1, osc2_det: 0.0, osc2_mix: 0.4, sub_vol: 0.45, unison: 4, spread: 0.15, filter_cut: 900.0, filter_res: 0.75, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.18, drive_type: 2, low_boost: 0.45, attack: 0.005, decay: 0.1, sustain: 0.85, release: 0.12, lfo_rate: 12.0, lfo_depth: 0.7, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.08, reverb_size: 0.35, ..PresetData::default() },
+        PresetData { name: "Square Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 2, osc1_det: 0.0, osc2_wave: 1, osc2_det: 5.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 3, spread: 0.2, filter_cut: 750.0, filter_res: 0.68, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.5, attack: 0.01, decay: 0.12, sustain: 0.8, release: 0.15, lfo_rate: 6.0, lfo_depth: 0.85, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.28, delay_fb: 0.32, reverb_mix: 0.1, reverb_size: 0.4, lfo_sync: 1, lfo_div: 9, ..PresetData::default() },
+        PresetData { name: "Morphing Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: -10.0, osc2_wave: 2, osc2_det: 10.0, osc2_mix: 0.5, sub_vol: 0.4, unison: 5, spread: 0.3, filter_cut: 850.0, filter_res: 0.72, filter_env: 0.03, filter_type: 0, filter_slope: 1, drive: 0.16, drive_type: 2, low_boost: 0.48, attack: 0.015, decay: 0.12, sustain: 0.78, release: 0.18, lfo_rate: 3.0, lfo_depth: 0.82, lfo_wave: 3, lfo_target: 1, porta: 0.0, delay_mix: 0.08, delay_time: 0.32, delay_fb: 0.38, reverb_mix: 0.12, reverb_size: 0.45, lfo_sync: 1, lfo_div: 8, ..PresetData::default() },
+        PresetData { name: "Stutter Wobble".into(), category: PresetCategory::Wobble, osc1_wave: 1, osc1_det: -6.0, osc2_wave: 2, osc2_det: 6.0, osc2_mix: 0.5, sub_vol: 0.5, unison: 4, spread: 0.2, filter_cut: 700.0, filter_res: 0.7, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.15, drive_type: 2, low_boost: 0.5, attack: 0.005, decay: 0.1, sustain: 0.85, release: 0.12, lfo_rate: 2.0, lfo_depth: 0.8, lfo_wave: 7, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.08, reverb_size: 0.4, lfo_sync: 1, lfo_div: 4, lfo_shapes: [stutter, LfoShape::default(), LfoShape::default()], ..PresetData::default() },
+        // Growl (6)
+        PresetData { name: "Growl".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -15.0, osc2_wave: 2, osc2_det: 15.0, osc2_mix: 0.6, sub_vol: 0.35, unison: 6, spread: 0.4, filter_cut: 700.0, filter_res: 0.75, filter_env: 0.0, filter_type: 0, filter_slope: 1, drive: 0.155, drive_type: 1, low_boost: 0.4, attack: 0.01, decay: 0.1, sustain: 0.8, release: 0.15, lfo_rate: 8.0, lfo_depth: 0.6, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.35, ..PresetData::default() },
+        PresetData { name: "Aggressive".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -20.0, osc2_wave: 1, osc2_det: 20.0, osc2_mix: 0.55, sub_vol: 0.3, unison: 7, spread: 0.45, filter_cut: 800.0, filter_res: 0.8, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.1, drive_type: 1, low_boost: 0.35, attack: 0.005, decay: 0.08, sustain: 0.85, release: 0.12, lfo_rate: 10.0, lfo_depth: 0.65, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.0, delay_time: 0.3, delay_fb: 0.3, reverb_mix: 0.03, reverb_size: 0.3, ..PresetData::default() },
+        PresetData { name: "Screamer".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -25.0, osc2_wave: 2, osc2_det: 25.0, osc2_mix: 0.65, sub_vol: 0.25, unison: 8, spread: 0.5, filter_cut: 1000.0, filter_res: 0.85, filter_env: 0.025, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 3, low_boost: 0.3, attack: 0.003, decay: 0.1, sustain: 0.9, release: 0.1, lfo_rate: 12.0, lfo_depth: 0.7, lfo_wave: 1, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.2, delay_fb: 0.35, reverb_mix: 0.05, reverb_size: 0.35, ..PresetData::default() },
+        PresetData { name: "Metallic".into(), category: PresetCategory::Growl, osc1_wave: 2, osc1_det: -30.0, osc2_wave: 2, osc2_det: 30.0, osc2_mix: 0.5, sub_vol: 0.2, unison: 6, spread: 0.35, filter_cut: 1200.0, filter_res: 0.7, filter_env: 0.03, filter_type: 0, filter_slope: 1, drive: 0.158, drive_type: 1, low_boost: 0.25, attack: 0.001, decay: 0.15, sustain: 0.7, release: 0.15, lfo_rate: 15.0, lfo_depth: 0.5, lfo_wave: 2, lfo_target: 1, porta: 0.0, delay_mix: 0.1, delay_time: 0.15, delay_fb: 0.4, reverb_mix: 0.08, reverb_size: 0.4, ..PresetData::default() },
+        PresetData { name: "Chaos".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -35.0, osc2_wave: 2, osc2_det: 35.0, osc2_mix: 0.6, sub_vol: 0.2, unison: 8, spread: 0.5, filter_cut: 900.0, filter_res: 0.82, filter_env: 0.07, filter_type: 0, filter_slope: 1, drive: 0.12, drive_type: 3, low_boost: 0.25, attack: 0.002, decay: 0.12, sustain: 0.88, release: 0.1, lfo_rate: 8.0, lfo_depth: 0.9, lfo_wave: 1, lfo_target: 1, porta: 0.0, delay_mix: 0.08, delay_time: 0.18, delay_fb: 0.45, reverb_mix: 0.1, reverb_size: 0.45, ..PresetData::default() },
+        PresetData { name: "Yoi Talker".into(), category: PresetCategory::Growl, osc1_wave: 1, osc1_det: -12.0, osc2_wave: 2, osc2_det: 12.0, osc2_mix: 0.5, sub_vol: 0.35, unison: 4, spread: 0.3, filter_cut: 1000.0, filter_res: 0.7, filter_env: 0.0, filter_type: 7, filter_slope: 1, drive: 0.14, drive_type: 1, low_boost: 0.4, attack: 0.005, decay: 0.25, sustain: 0.7, release: 0.15, lfo_rate: 3.0, lfo_depth: 0.6, lfo_wave: 3, lfo_target: 3, porta: 0.0, delay_mix: 0.05, delay_time: 0.25, delay_fb: 0.3, reverb_mix: 0.05, reverb_size: 0.35, vowel: 0.55, vowel_env: -0.5, ..PresetData::default() },
+        // Clean (5)
+        PresetData { name: "Clean Finger".into(), category: PresetCategory::Clean, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 3, osc2_det: 0.0, osc2_mix: 0.3, sub_vol: 0.4, unison: 1, spread: 0.0, filter_cut: 1500.0, filter_res: 0.2, filter_env: 0.025, filter_type: 0, filter_slope: 0, drive: 0.0, drive_type: 0, low_boost: 0.3, attack: 0.005, decay: 0.3, sustain: 0.5, release: 0.3, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.1, delay_time: 0.35, delay_fb: 0.3, reverb_mix: 0.15, reverb_size: 0.5, ..PresetData::default() },
+        PresetData { name: "Soft Synth".into(), category: PresetCategory::Clean, osc1_wave: 0, osc1_det: 0.0, osc2_wave: 0, osc2_det: 5.0, osc2_mix: 0.4, sub_vol: 0.5, unison: 2, spread: 0.1, filter_cut: 1200.0, filter_res: 0.25, filter_env: 0.03, filter_type: 0, filter_slope: 0, drive: 0.05, drive_type: 0, low_boost: 0.4, attack: 0.02, decay: 0.2, sustain: 0.6, release: 0.35, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.005, delay_mix: 0.12, delay_time: 0.4, delay_fb: 0.35, reverb_mix: 0.2, reverb_size: 0.55, ..PresetData::default() },
+        PresetData { name: "Mellow".into(), category: PresetCategory::Clean, osc1_wave: 3, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.35, sub_vol: 0.45, unison: 1, spread: 0.0, filter_cut: 800.0, filter_res: 0.15, filter_env: 0.025, filter_type: 0, filter_slope: 0, drive: 0.0, drive_type: 0, low_boost: 0.35, attack: 0.03, decay: 0.25, sustain: 0.55, release: 0.4, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.007, delay_mix: 0.08, delay_time: 0.38, delay_fb: 0.28, reverb_mix: 0.25, reverb_size: 0.6, ..PresetData::default() },
+        PresetData { name: "Warm DI".into(), category: PresetCategory::Clean, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 0, osc2_det: 0.0, osc2_mix: 0.2, sub_vol: 0.55, unison: 1, spread: 0.0, filter_cut: 1000.0, filter_res: 0.2, filter_env: 0.07, filter_type: 0, filter_slope: 0, drive: 0.1, drive_type: 2, low_boost: 0.5, attack: 0.01, decay: 0.2, sustain: 0.65, release: 0.25, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.05, delay_time: 0.3, delay_fb: 0.25, reverb_mix: 0.1, reverb_size: 0.45, ..PresetData::default() },
+        PresetData { name: "Analog Pluck".into(), category: PresetCategory::Clean, osc1_wave: 1, osc1_det: 0.0, osc2_wave: 2, osc2_det: 3.0, osc2_mix: 0.3, sub_vol: 0.5, unison: 1, spread: 0.0, filter_cut: 700.0, filter_res: 0.3, filter_env: 0.1, filter_type: 0, filter_slope: 1, drive: 0.08, drive_type: 2, low_boost: 0.45, attack: 0.002, decay: 0.35, sustain: 0.2, release: 0.3, lfo_rate: 0.0, lfo_depth: 0.0, lfo_wave: 0, lfo_target: 1, porta: 0.0, delay_mix: 0.06, delay_time: 0.3, delay_fb: 0.25, reverb_mix: 0.08, reverb_size: 0.4, env_mode: 1, ..PresetData::default() },
     ]
 }
 
@@ -101,6 +155,28 @@ struct BassParams {
     #[id = "filter_env_amount"] pub filter_env_amount: FloatParam,
     #[id = "filter_type"] pub filter_type: IntParam,
     #[id = "filter_slope"] pub filter_slope: IntParam,
+    #[id = "filter_model"] pub filter_model: IntParam,
+    #[id = "accent"] pub accent: FloatParam,
+    #[id = "filter_keytrack"] pub filter_keytrack: FloatParam,
+    #[id = "filter_velocity"] pub filter_velocity: FloatParam,
+    #[id = "filter_key_center"] pub filter_key_center: IntParam,
+    #[id = "filter2_type"] pub filter2_type: IntParam,
+    #[id = "filter2_cutoff"] pub filter2_cutoff: FloatParam,
+    #[id = "filter2_resonance"] pub filter2_resonance: FloatParam,
+    #[id = "filter_routing"] pub filter_routing: IntParam,
+    #[id = "vowel"] pub vowel: FloatParam,
+    #[id = "vowel_env"] pub vowel_env: FloatParam,
+    #[id = "amp_attack_curve"] pub amp_attack_curve: FloatParam,
+    #[id = "amp_decay_curve"] pub amp_decay_curve: FloatParam,
+    #[id = "amp_release_curve"] pub amp_release_curve: FloatParam,
+    #[id = "env_mode"] pub env_mode: IntParam,
+    #[id = "pitch_env_amount"] pub pitch_env_amount: FloatParam,
+    #[id = "pitch_env_decay"] pub pitch_env_decay: FloatParam,
+    #[id = "velocity_amp"] pub velocity_amp: FloatParam,
+    #[id = "velocity_curve"] pub velocity_curve: IntParam,
+    #[id = "env_retrigger"] pub env_retrigger: IntParam,
+    #[id = "amp_delay"] pub amp_delay: FloatParam,
+    #[id = "amp_hold"] pub amp_hold: FloatParam,
     #[id = "drive"] pub drive: FloatParam,
     #[id = "drive_type"] pub drive_type: IntParam,
     #[id = "low_boost"] pub low_boost: FloatParam,
@@ -108,11 +184,24 @@ struct BassParams {
     #[id = "amp_decay"] pub amp_decay: FloatParam,
     #[id = "amp_sustain"] pub amp_sustain: FloatParam,
     #[id = "amp_release"] pub amp_release: FloatParam,
-    #[id = "lfo_rate"] pub lfo_rate: FloatParam,
-    #[id = "lfo_depth"] pub lfo_depth: FloatParam,
-    #[id = "lfo_waveform"] pub lfo_waveform: IntParam,
-    #[id = "lfo_target"] pub lfo_target: IntParam,
+    #[nested(group = "LFO 1")] pub lfo1: LfoParams,
+    #[nested(id_prefix = "lfo2", group = "LFO 2")] pub lfo2: LfoParams,
+    #[nested(id_prefix = "lfo3", group = "LFO 3")] pub lfo3: LfoParams,
+    #[persist = "lfo-shapes"] pub lfo_shapes: Arc<Mutex<[LfoShape; 3]>>,
+    #[persist = "midi-map"] pub midi_map: Arc<Mutex<Vec<MidiMapping>>>,
+    #[persist = "tuning"] pub tuning: Arc<Mutex<Tuning>>,
+    #[nested(array, group = "Mod Matrix")] pub mod_slots: [ModSlotParams; MOD_SLOTS],
+    #[nested(array, group = "Macros")] pub macros: [MacroParams; MACROS],
     #[id = "portamento"] pub portamento: FloatParam,
+    #[id = "glide_type"] pub glide_type: IntParam,
+    #[id = "glide_legato"] pub glide_legato: IntParam,
+    #[id = "bend_up"] pub bend_up: IntParam,
+    #[id = "bend_down"] pub bend_down: IntParam,
+    #[id = "mpe_mode"] pub mpe_mode: IntParam,
+    #[id = "mpe_bend_range"] pub mpe_bend_range: IntParam,
+    #[id = "mpe_pressure_amp"] pub mpe_pressure_amp: FloatParam,
+    #[id = "mpe_pressure_cutoff"] pub mpe_pressure_cutoff: FloatParam,
+    #[id = "mpe_timbre_cutoff"] pub mpe_timbre_cutoff: FloatParam,
     #[id = "arp_on"] pub arp_on: IntParam,
     #[id = "arp_mode"] pub arp_mode: IntParam,
     #[id = "arp_rate"] pub arp_rate: IntParam,
@@ -123,12 +212,14 @@ struct BassParams {
     #[id = "reverb_mix"] pub reverb_mix: FloatParam,
     #[id = "reverb_size"] pub reverb_size: FloatParam,
     #[id = "master_gain"] pub master_gain: FloatParam,
+    #[id = "master_tune"] pub master_tune: FloatParam,
+    #[id = "oversampling"] pub oversampling: IntParam,
 }
 
 impl Default for BassParams {
     fn default() -> Self {
         Self {
-            editor_state: EguiState::from_size(680, 580),
+            editor_state: EguiState::from_size(680, 604),
             osc1_waveform: IntParam::new("Wave1", 1, IntRange::Linear { min: 0, max: 3 }),
             osc1_detune: FloatParam::new("Det1", 0.0, FloatRange::Linear { min: -100.0, max: 100.0 }),
             osc2_waveform: IntParam::new("Wave2", 1, IntRange::Linear { min: 0, max: 3 }),
@@ -137,23 +228,58 @@ impl Default for BassParams {
             sub_volume: FloatParam::new("SubVol", 0.5, FloatRange::Linear { min: 0.0, max: 1.0 }),
             unison_voices: IntParam::new("Unison", 4, IntRange::Linear { min: 1, max: 8 }),
             unison_spread: FloatParam::new("Spread", 0.25, FloatRange::Linear { min: 0.0, max: 1.0 }),
-            filter_cutoff: FloatParam::new("Cutoff", 600.0, FloatRange::Skewed { min: 20.0, max: 20000.0, factor: FloatRange::skew_factor(-2.0) }),
-            filter_resonance: FloatParam::new("Reso", 0.4, FloatRange::Linear { min: 0.0, max: 0.99 }),
+            filter_cutoff: FloatParam::new("Cutoff", 600.0, FloatRange::Skewed { min: 20.0, max: 20000.0, factor: FloatRange::skew_factor(-2.0) }).with_poly_modulation_id(POLY_MOD_CUTOFF),
+            filter_resonance: FloatParam::new("Reso", 0.4, FloatRange::Linear { min: 0.0, max: 0.99 }).with_poly_modulation_id(POLY_MOD_RESO),
             filter_env_amount: FloatParam::new("FltEnv", 0.07, FloatRange::Linear { min: -1.0, max: 1.0 }),
-            filter_type: IntParam::new("Type", 0, IntRange::Linear { min: 0, max: 2 }),
+            filter_type: IntParam::new("Type", 0, IntRange::Linear { min: 0, max: FILTER_VOWEL }),
             filter_slope: IntParam::new("Slope", 1, IntRange::Linear { min: 0, max: 1 }),
-            drive: FloatParam::new("Drive", 0.1, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            filter_model: IntParam::new("Model", 0, IntRange::Linear { min: 0, max: 2 }),
+            accent: FloatParam::new("Accent", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            filter_keytrack: FloatParam::new("KeyTrk", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            filter_velocity: FloatParam::new("FltVel", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            filter_key_center: IntParam::new("KeyCtr", 60, IntRange::Linear { min: 0, max: 127 }),
+            filter2_type: IntParam::new("Type2", 0, IntRange::Linear { min: 0, max: 4 }),
+            filter2_cutoff: FloatParam::new("Cutoff2", 2000.0, FloatRange::Skewed { min: 20.0, max: 20000.0, factor: FloatRange::skew_factor(-2.0) }),
+            filter2_resonance: FloatParam::new("Reso2", 0.2, FloatRange::Linear { min: 0.0, max: 0.99 }),
+            filter_routing: IntParam::new("Routing", 0, IntRange::Linear { min: 0, max: 3 }),
+            vowel: FloatParam::new("Vowel", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            vowel_env: FloatParam::new("VowEnv", 0.0, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            amp_attack_curve: FloatParam::new("ACurve", 0.0, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            amp_decay_curve: FloatParam::new("DCurve", 0.0, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            amp_release_curve: FloatParam::new("RCurve", 0.0, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            env_mode: IntParam::new("EnvMode", 0, IntRange::Linear { min: 0, max: 1 }),
+            pitch_env_amount: FloatParam::new("PEnv", 0.0, FloatRange::Linear { min: -48.0, max: 48.0 }),
+            pitch_env_decay: FloatParam::new("PDec", 0.1, FloatRange::Skewed { min: 0.001, max: 2.0, factor: FloatRange::skew_factor(-2.0) }),
+            velocity_amp: FloatParam::new("VelAmp", 1.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            velocity_curve: IntParam::new("VelCrv", 0, IntRange::Linear { min: 0, max: 3 }),
+            env_retrigger: IntParam::new("Retrig", 0, IntRange::Linear { min: 0, max: 2 }),
+            amp_delay: FloatParam::new("Dly", 0.0, FloatRange::Skewed { min: 0.0, max: 2.0, factor: FloatRange::skew_factor(-2.0) }),
+            amp_hold: FloatParam::new("Hold", 0.0, FloatRange::Skewed { min: 0.0, max: 2.0, factor: FloatRange::skew_factor(-2.0) }),
+            drive: FloatParam::new("Drive", 0.1, FloatRange::Linear { min: 0.0, max: 1.0 }).with_poly_modulation_id(POLY_MOD_DRIVE),
             drive_type: IntParam::new("DriveType", 2, IntRange::Linear { min: 0, max: 3 }),
             low_boost: FloatParam::new("LowBoost", 0.5, FloatRange::Linear { min: 0.0, max: 1.0 }),
             amp_attack: FloatParam::new("Atk", 0.005, FloatRange::Skewed { min: 0.001, max: 5.0, factor: FloatRange::skew_factor(-2.0) }),
             amp_decay: FloatParam::new("Dec", 0.15, FloatRange::Skewed { min: 0.001, max: 5.0, factor: FloatRange::skew_factor(-2.0) }),
             amp_sustain: FloatParam::new("Sus", 0.7, FloatRange::Linear { min: 0.0, max: 1.0 }),
             amp_release: FloatParam::new("Rel", 0.15, FloatRange::Skewed { min: 0.001, max: 10.0, factor: FloatRange::skew_factor(-2.0) }),
-            lfo_rate: FloatParam::new("Rate", 2.0, FloatRange::Skewed { min: 0.01, max: 50.0, factor: FloatRange::skew_factor(-1.5) }),
-            lfo_depth: FloatParam::new("Depth", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
-            lfo_waveform: IntParam::new("LfoWv", 0, IntRange::Linear { min: 0, max: 3 }),
-            lfo_target: IntParam::new("Target", 1, IntRange::Linear { min: 0, max: 2 }),
+            lfo1: LfoParams::new(1),
+            lfo2: LfoParams::new(0),
+            lfo3: LfoParams::new(2),
+            lfo_shapes: Arc::new(Mutex::new([LfoShape::default(); 3])),
+            midi_map: Arc::new(Mutex::new(Vec::new())),
+            tuning: Arc::new(Mutex::new(Tuning::default())),
+            mod_slots: default_mod_slots().map(ModSlotParams::new),
+            macros: std::array::from_fn(|i| MacroParams::new(MACRO_NAMES[i], &default_macros()[i])),
             portamento: FloatParam::new("Porta", 0.007, FloatRange::Skewed { min: 0.0, max: 1.0, factor: FloatRange::skew_factor(-1.5) }),
+            glide_type: IntParam::new("GlideType", 0, IntRange::Linear { min: 0, max: 2 }),
+            glide_legato: IntParam::new("GlideLegato", 0, IntRange::Linear { min: 0, max: 1 }),
+            bend_up: IntParam::new("BendUp", 2, IntRange::Linear { min: 0, max: MAX_BEND_RANGE }),
+            bend_down: IntParam::new("BendDown", 2, IntRange::Linear { min: 0, max: MAX_BEND_RANGE }),
+            mpe_mode: IntParam::new("MPE", 0, IntRange::Linear { min: 0, max: 1 }),
+            mpe_bend_range: IntParam::new("MPEBend", 48, IntRange::Linear { min: 0, max: MAX_MPE_BEND_RANGE }),
+            mpe_pressure_amp: FloatParam::new("MPEPressAmp", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            mpe_pressure_cutoff: FloatParam::new("MPEPressCut", 0.0, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            mpe_timbre_cutoff: FloatParam::new("MPETimbreCut", 0.5, FloatRange::Linear { min: -1.0, max: 1.0 }),
             arp_on: IntParam::new("ArpOn", 0, IntRange::Linear { min: 0, max: 1 }),
             arp_mode: IntParam::new("ArpMode", 0, IntRange::Linear { min: 0, max: 3 }),
             arp_rate: IntParam::new("ArpRate", 1, IntRange::Linear { min: 0, max: 3 }),
@@ -163,17 +289,355 @@ impl Default for BassParams {
             delay_feedback: FloatParam::new("DlyFB", 0.4, FloatRange::Linear { min: 0.0, max: 0.95 }),
             reverb_mix: FloatParam::new("RevMix", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
             reverb_size: FloatParam::new("RevSize", 0.5, FloatRange::Linear { min: 0.1, max: 0.99 }),
-            master_gain: FloatParam::new("Vol", 0.6, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            master_gain: FloatParam::new("Vol", 0.6, FloatRange::Linear { min: 0.0, max: 1.0 }).with_poly_modulation_id(POLY_MOD_GAIN),
+            master_tune: FloatParam::new("Tune", 440.0, FloatRange::Linear { min: 415.0, max: 466.0 }),
+            oversampling: IntParam::new("Oversample", 0, IntRange::Linear { min: 0, max: 2 }),
+        }
+    }
+}
+
+impl BassParams {
+    fn lfos(&self) -> [&LfoParams; 3] { [&self.lfo1, &self.lfo2, &self.lfo3] }
+}
+
+// One LFO. LFO 1 has no ID prefix so it keeps the IDs of the original single LFO
+#[derive(Params)]
+struct LfoParams {
+    #[id = "lfo_rate"] pub rate: FloatParam,
+    #[id = "lfo_depth"] pub depth: FloatParam,
+    #[id = "lfo_waveform"] pub waveform: IntParam,
+    #[id = "lfo_draw"] pub draw: IntParam,
+    #[id = "lfo_target"] pub target: IntParam,
+    #[id = "lfo_sync"] pub sync: IntParam,
+    #[id = "lfo_division"] pub division: IntParam,
+    #[id = "lfo_mode"] pub mode: IntParam,
+    #[id = "lfo_start_phase"] pub start_phase: FloatParam,
+    #[id = "lfo_fade"] pub fade: FloatParam,
+}
+
+impl LfoParams {
+    fn new(target: i32) -> Self {
+        Self {
+            rate: FloatParam::new("Rate", 2.0, FloatRange::Skewed { min: 0.01, max: 50.0, factor: FloatRange::skew_factor(-1.5) }),
+            depth: FloatParam::new("Depth", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            waveform: IntParam::new("LfoWv", 0, IntRange::Linear { min: 0, max: LFO_DRAWN - 1 }),
+            draw: IntParam::new("LfoDraw", 0, IntRange::Linear { min: 0, max: 1 }),
+            target: IntParam::new("Target", target, IntRange::Linear { min: 0, max: 3 }),
+            sync: IntParam::new("LfoSync", 0, IntRange::Linear { min: 0, max: 1 }),
+            division: IntParam::new("LfoDiv", 7, IntRange::Linear { min: 0, max: 13 }),
+            mode: IntParam::new("LfoMode", 0, IntRange::Linear { min: 0, max: 2 }),
+            start_phase: FloatParam::new("Phase", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            fade: FloatParam::new("Fade", 0.0, FloatRange::Skewed { min: 0.0, max: 5.0, factor: FloatRange::skew_factor(-2.0) }),
+        }
+    }
+
+    // The drawn shape overrides the waveform while draw is on
+    fn wave(&self) -> i32 { if self.draw.value() == 1 { LFO_DRAWN } else { self.waveform.value() } }
+
+    fn to_preset(&self) -> LfoPreset {
+        LfoPreset {
+            rate: self.rate.value(), depth: self.depth.value(), wave: self.wave(), target: self.target.value(),
+            sync: self.sync.value(), div: self.division.value(), mode: self.mode.value(), phase: self.start_phase.value(), fade: self.fade.value(),
+        }
+    }
+
+    fn load_preset(&self, setter: &ParamSetter, p: &LfoPreset) {
+        setter.set_parameter_normalized(&self.rate, normalize_lfo_rate(p.rate));
+        setter.set_parameter_normalized(&self.depth, p.depth);
+        if p.wave != LFO_DRAWN { setter.set_parameter_normalized(&self.waveform, p.wave as f32 / (LFO_DRAWN - 1) as f32); }
+        setter.set_parameter_normalized(&self.draw, if p.wave == LFO_DRAWN { 1.0 } else { 0.0 });
+        setter.set_parameter_normalized(&self.target, p.target as f32 / 3.0);
+        setter.set_parameter_normalized(&self.sync, p.sync as f32);
+        setter.set_parameter_normalized(&self.division, p.div as f32 / 13.0);
+        setter.set_parameter_normalized(&self.mode, p.mode as f32 / 2.0);
+        setter.set_parameter_normalized(&self.start_phase, p.phase);
+        setter.set_parameter_normalized(&self.fade, self.fade.preview_normalized(p.fade));
+    }
+}
+
+// ========== MOD MATRIX ==========
+// One mod matrix slot; IDs get the slot number appended (mod_src_1, ...)
+#[derive(Params)]
+struct ModSlotParams {
+    #[id = "mod_src"] pub source: IntParam,
+    #[id = "mod_dst"] pub dest: IntParam,
+    #[id = "mod_amt"] pub amount: FloatParam,
+    #[id = "mod_via"] pub via: IntParam,
+}
+
+impl ModSlotParams {
+    fn new(m: ModSlot) -> Self {
+        Self {
+            source: IntParam::new("ModSrc", m.source, IntRange::Linear { min: 0, max: MOD_SOURCE_NAMES.len() as i32 - 1 }),
+            dest: IntParam::new("ModDst", m.dest, IntRange::Linear { min: 0, max: MOD_DEST_NAMES.len() as i32 - 1 }),
+            amount: FloatParam::new("ModAmt", m.amount, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            via: IntParam::new("ModVia", m.via, IntRange::Linear { min: 0, max: MOD_SOURCE_NAMES.len() as i32 - 1 }),
+        }
+    }
+
+    fn to_slot(&self) -> ModSlot { ModSlot { source: self.source.value(), dest: self.dest.value(), amount: self.amount.value(), via: self.via.value() } }
+
+    fn load_slot(&self, setter: &ParamSetter, m: &ModSlot) {
+        setter.set_parameter(&self.source, m.source);
+        setter.set_parameter(&self.dest, m.dest);
+        setter.set_parameter(&self.amount, m.amount);
+        setter.set_parameter(&self.via, m.via);
+    }
+}
+
+const MOD_SLOTS: usize = 8;
+const MOD_SOURCE_NAMES: [&str; 11] = ["--", "LFO 1", "LFO 2", "LFO 3", "Amp Env", "Pitch Env", "Velocity", "Key", "Mod Wheel", "Aftertouch", "Poly AT"];
+const MOD_SRC_LFO1: i32 = 1;
+const MOD_SRC_AMP_ENV: i32 = 4;
+const MOD_SRC_PITCH_ENV: i32 = 5;
+const MOD_SRC_VELOCITY: i32 = 6;
+const MOD_SRC_KEY: i32 = 7;
+const MOD_SRC_MOD_WHEEL: i32 = 8;
+const MOD_SRC_AFTERTOUCH: i32 = 9;
+const MOD_SRC_POLY_AT: i32 = 10;
+// LFO rate/depth/phase/fade, the effects, Volume and Dly Time are evaluated once per sample,
+// with envelopes, velocity, key and poly AT taken from the newest voice; the rest per voice
+// New destinations go at the end so saved slots keep pointing at the same parameter
+const MOD_DEST_NAMES: [&str; 53] = [
+    "--", "Cutoff", "Reso", "Flt Env", "Cutoff 2", "Reso 2", "Vowel", "Drive", "Osc1 Det", "Osc2 Det", "Osc2 Mix", "Sub Vol", "Spread",
+    "LFO1 Rate", "LFO1 Depth", "LFO2 Rate", "LFO2 Depth", "LFO3 Rate", "LFO3 Depth", "Low+", "Dly Mix", "Dly FB", "Rev Mix", "Rev Size", "Volume",
+    "Env Dly", "Attack", "Hold", "Decay", "Sustain", "Release", "A Curve", "D Curve", "R Curve",
+    "P.Env Amt", "P.Env Dec", "Accent", "Vowel Env", "Key Trk", "Flt Vel", "Vel Amp", "Porta", "Dly Time",
+    "Tune", "MPE P>Amp", "MPE P>Cut", "MPE T>Cut", "LFO1 Phase", "LFO1 Fade", "LFO2 Phase", "LFO2 Fade", "LFO3 Phase", "LFO3 Fade",
+];
+const MOD_DEST_COUNT: usize = MOD_DEST_NAMES.len();
+const MOD_DEST_CUTOFF: usize = 1;
+const MOD_DEST_RESO: usize = 2;
+const MOD_DEST_FLT_ENV: usize = 3;
+const MOD_DEST_CUTOFF2: usize = 4;
+const MOD_DEST_RESO2: usize = 5;
+const MOD_DEST_VOWEL: usize = 6;
+const MOD_DEST_DRIVE: usize = 7;
+const MOD_DEST_OSC1_DET: usize = 8;
+const MOD_DEST_OSC2_DET: usize = 9;
+const MOD_DEST_OSC2_MIX: usize = 10;
+const MOD_DEST_SUB_VOL: usize = 11;
+const MOD_DEST_SPREAD: usize = 12;
+// LFO n rate is MOD_DEST_LFO1_RATE + 2 * n, its depth the slot after
+const MOD_DEST_LFO1_RATE: usize = 13;
+const MOD_DEST_LOW_BOOST: usize = 19;
+const MOD_DEST_DELAY_MIX: usize = 20;
+const MOD_DEST_DELAY_FB: usize = 21;
+const MOD_DEST_REVERB_MIX: usize = 22;
+const MOD_DEST_REVERB_SIZE: usize = 23;
+const MOD_DEST_VOLUME: usize = 24;
+const MOD_DEST_ENV_DELAY: usize = 25;
+const MOD_DEST_ATTACK: usize = 26;
+const MOD_DEST_HOLD: usize = 27;
+const MOD_DEST_DECAY: usize = 28;
+const MOD_DEST_SUSTAIN: usize = 29;
+const MOD_DEST_RELEASE: usize = 30;
+const MOD_DEST_ATK_CURVE: usize = 31;
+const MOD_DEST_DEC_CURVE: usize = 32;
+const MOD_DEST_REL_CURVE: usize = 33;
+const MOD_DEST_PITCH_ENV: usize = 34;
+const MOD_DEST_PITCH_DECAY: usize = 35;
+const MOD_DEST_ACCENT: usize = 36;
+const MOD_DEST_VOWEL_ENV: usize = 37;
+const MOD_DEST_KEYTRACK: usize = 38;
+const MOD_DEST_FLT_VEL: usize = 39;
+const MOD_DEST_VEL_AMP: usize = 40;
+const MOD_DEST_PORTA: usize = 41;
+const MOD_DEST_DELAY_TIME: usize = 42;
+const MOD_DEST_TUNE: usize = 43;
+const MOD_DEST_MPE_PRESS_AMP: usize = 44;
+const MOD_DEST_MPE_PRESS_CUT: usize = 45;
+const MOD_DEST_MPE_TIMBRE_CUT: usize = 46;
+// LFO n start phase is MOD_DEST_LFO1_PHASE + 2 * n, its fade time the slot after
+const MOD_DEST_LFO1_PHASE: usize = 47;
+// Rate modulation on a synced LFO steps the division in whole octaves, up to this many
+const LFO_SYNC_MOD_OCTAVES: f32 = 4.0;
+
+// Source, destination, amount (normalized units of the destination) and an optional via source
+#[derive(Clone, Copy, Default)]
+struct ModSlot { source: i32, dest: i32, amount: f32, via: i32 }
+
+// Current value of every mod source; voice sources stay at zero until a voice fills them in
+#[derive(Clone, Copy, Default)]
+struct ModSources { lfo: [f32; 3], amp_env: f32, pitch_env: f32, velocity: f32, key: f32, mod_wheel: f32, aftertouch: f32, poly_at: f32 }
+
+impl ModSources {
+    fn get(&self, src: i32) -> f32 {
+        match src {
+            MOD_SRC_AMP_ENV => self.amp_env,
+            MOD_SRC_PITCH_ENV => self.pitch_env,
+            MOD_SRC_VELOCITY => self.velocity,
+            MOD_SRC_KEY => self.key,
+            MOD_SRC_MOD_WHEEL => self.mod_wheel,
+            MOD_SRC_AFTERTOUCH => self.aftertouch,
+            MOD_SRC_POLY_AT => self.poly_at,
+            s if (MOD_SRC_LFO1..MOD_SRC_LFO1 + 3).contains(&s) => self.lfo[(s - MOD_SRC_LFO1) as usize],
+            _ => 0.0,
+        }
+    }
+}
+
+fn is_voice_source(src: i32) -> bool { (MOD_SRC_AMP_ENV..=MOD_SRC_KEY).contains(&src) || src == MOD_SRC_POLY_AT }
+
+// Destinations evaluated once per sample rather than per voice
+fn is_global_dest(dest: usize) -> bool {
+    (MOD_DEST_LFO1_RATE..MOD_DEST_LFO1_RATE + 6).contains(&dest) || (MOD_DEST_LOW_BOOST..=MOD_DEST_VOLUME).contains(&dest)
+        || dest == MOD_DEST_DELAY_TIME || (MOD_DEST_LFO1_PHASE..MOD_DEST_LFO1_PHASE + 6).contains(&dest)
+}
+
+// Performance controllers come wired up: wheel deepens LFO 1, pressure opens the filter
+fn default_mod_slots() -> [ModSlot; MOD_SLOTS] {
+    let mut slots = [ModSlot::default(); MOD_SLOTS];
+    slots[0] = ModSlot { source: MOD_SRC_MOD_WHEEL, dest: MOD_DEST_LFO1_RATE as i32 + 1, amount: 1.0, via: 0 };
+    slots[1] = ModSlot { source: MOD_SRC_AFTERTOUCH, dest: MOD_DEST_CUTOFF as i32, amount: 0.3, via: 0 };
+    slots[2] = ModSlot { source: MOD_SRC_POLY_AT, dest: MOD_DEST_CUTOFF as i32, amount: 0.3, via: 0 };
+    slots
+}
+
+impl ModSlot {
+    fn active(&self) -> bool { self.source != 0 && self.dest != 0 && self.amount != 0.0 }
+    fn is_voice(&self) -> bool { is_voice_source(self.source) || is_voice_source(self.via) }
+    fn value(&self, src: &ModSources) -> f32 {
+        let via = if self.via == 0 { 1.0 } else { src.get(self.via) };
+        src.get(self.source) * self.amount * via
+    }
+}
+
+// base pushed by a normalized offset, or base untouched when there is none
+fn mod_param(p: &FloatParam, base: f32, offset: f32) -> f32 {
+    if offset == 0.0 { base } else { p.preview_plain((p.modulated_normalized_value() + offset).clamp(0.0, 1.0)) }
+}
+
+// ========== MACROS ==========
+const MACROS: usize = 4;
+const MACRO_ROUTES: usize = 4;
+const MACRO_NAMES: [&str; MACROS] = ["Growl", "Macro 2", "Macro 3", "Macro 4"];
+
+// A macro target: any mod matrix destination, pushed by amount of its range at full macro
+#[derive(Clone, Copy, Default)]
+struct MacroRoute { dest: i32, amount: f32 }
+
+#[derive(Clone, Copy, Default)]
+struct MacroPreset { value: f32, routes: [MacroRoute; MACRO_ROUTES] }
+
+// Growl opens the filter, adds drive and deepens LFO 1 in one move
+fn default_macros() -> [MacroPreset; MACROS] {
+    let mut macros = [MacroPreset::default(); MACROS];
+    macros[0].routes[0] = MacroRoute { dest: MOD_DEST_CUTOFF as i32, amount: 0.35 };
+    macros[0].routes[1] = MacroRoute { dest: MOD_DEST_DRIVE as i32, amount: 0.5 };
+    macros[0].routes[2] = MacroRoute { dest: MOD_DEST_LFO1_RATE as i32 + 1, amount: 0.6 };
+    macros
+}
+
+#[derive(Params)]
+struct MacroRouteParams {
+    #[id = "mac_dst"] pub dest: IntParam,
+    #[id = "mac_amt"] pub amount: FloatParam,
+}
+
+// One macro knob and its routes; IDs get the macro number appended (macro_1, mac_dst_2_1, ...)
+#[derive(Params)]
+struct MacroParams {
+    #[id = "macro"] pub value: FloatParam,
+    #[nested(array, group = "Route")] pub routes: [MacroRouteParams; MACRO_ROUTES],
+}
+
+impl MacroParams {
+    fn new(name: &str, m: &MacroPreset) -> Self {
+        Self {
+            value: FloatParam::new(name, m.value, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            routes: m.routes.map(|r| MacroRouteParams {
+                dest: IntParam::new("MacDst", r.dest, IntRange::Linear { min: 0, max: MOD_DEST_NAMES.len() as i32 - 1 }),
+                amount: FloatParam::new("MacAmt", r.amount, FloatRange::Linear { min: -1.0, max: 1.0 }),
+            }),
+        }
+    }
+
+    fn to_preset(&self) -> MacroPreset {
+        MacroPreset { value: self.value.value(), routes: std::array::from_fn(|i| MacroRoute { dest: self.routes[i].dest.value(), amount: self.routes[i].amount.value() }) }
+    }
+
+    fn load_preset(&self, setter: &ParamSetter, m: &MacroPreset) {
+        setter.set_parameter(&self.value, m.value);
+        for (r, p) in self.routes.iter().zip(&m.routes) {
+            setter.set_parameter(&r.dest, p.dest);
+            setter.set_parameter(&r.amount, p.amount);
         }
     }
 }
 
+// Offset per mod destination from every macro route, routes to the same destination add up
+fn macro_offsets(macros: &[MacroParams]) -> [f32; MOD_DEST_COUNT] {
+    let mut offsets = [0.0f32; MOD_DEST_COUNT];
+    for m in macros {
+        let v = m.value.value();
+        for r in &m.routes { offsets[r.dest.value() as usize] += v * r.amount.value(); }
+    }
+    offsets
+}
+
 // ========== VOICE ==========
 const MAX_VOICES: usize = 16;
+const GLIDE_TIME: i32 = 1;
+const GLIDE_EXP: i32 = 2;
+// CLAP poly modulation IDs, also indices into Voice::poly_mod
+const POLY_MOD_CUTOFF: u32 = 0;
+const POLY_MOD_RESO: u32 = 1;
+const POLY_MOD_DRIVE: u32 = 2;
+const POLY_MOD_GAIN: u32 = 3;
+const POLY_MOD_COUNT: usize = 4;
 const MAX_ARP_NOTES: usize = 16;
 const MAX_DELAY_SAMPLES: usize = 96000;
+const ACCENT_VELOCITY: f32 = 100.0 / 127.0;
+const MAX_BEND_RANGE: i32 = 24;
+const BEND_SMOOTH_TIME: f32 = 0.005;
+const CC_MOD_WHEEL: u8 = 1;
+const CC_SUSTAIN: u8 = 64;
+const CC_MPE_TIMBRE: u8 = 74;
+// Lower-zone MPE: channel 1 is the master, every other channel carries one note
+const MPE_MASTER_CHANNEL: u8 = 0;
+const MAX_MPE_BEND_RANGE: i32 = 96;
+// Long enough for a 20 Hz comb at 192 kHz with 4x oversampling
+const MAX_COMB_SAMPLES: usize = 38400;
+// Filter types after LP, HP, BP, notch and peak
+const FILTER_COMB_POS: i32 = 5;
+const FILTER_COMB_NEG: i32 = 6;
+const FILTER_VOWEL: i32 = 7;
+// F1-F3 centre (Hz) and gain for A, E, I, O, U; the vowel knob sweeps through them in order
+const VOWEL_FORMANTS: [[(f32, f32); 3]; 5] = [
+    [(800.0, 1.0), (1150.0, 0.5), (2900.0, 0.1)],
+    [(400.0, 1.0), (1600.0, 0.35), (2700.0, 0.2)],
+    [(300.0, 1.0), (2250.0, 0.2), (3000.0, 0.15)],
+    [(450.0, 1.0), (800.0, 0.4), (2830.0, 0.08)],
+    [(325.0, 1.0), (700.0, 0.25), (2530.0, 0.05)],
+];
+const FILTER_ROUTE_SERIAL: i32 = 1;
+const FILTER_ROUTE_PARALLEL: i32 = 2;
+const FILTER_ROUTE_SPLIT: i32 = 3;
+// Full velocity with the amount at 100% opens the cutoff by this many octaves
+const FILTER_VEL_OCTAVES: f32 = 3.0;
+// RC mode: the attack charges towards an overshoot target and stops at 1.0, like
+// an analog envelope; decay and release cover this many time constants per stage
+const RC_ATTACK_TARGET: f32 = 1.3;
+const RC_STAGE_TCS: f32 = 4.6;
+const RETRIG_RESTART: i32 = 1;
+const RETRIG_LEGATO: i32 = 2;
+// Envelope stages past release, so 1-4 keep meaning A/D/S/R
+const ENV_DELAY: u8 = 5;
+const ENV_HOLD: u8 = 6;
+const LFO_FREE: i32 = 0;
+const LFO_ONE_SHOT: i32 = 2;
+// One-shot LFOs stop just short of the wrap so they hold the cycle's last value
+const LFO_ONE_SHOT_END: f64 = 0.999_999;
+const LFO_SAMPLE_HOLD: i32 = 5;
+const LFO_SMOOTH_RANDOM: i32 = 6;
+const LFO_DRAWN: i32 = 7;
+const MAX_LFO_POINTS: usize = 16;
 const PANEL_WIDTH: f32 = 284.0;
+// Spans a row of two panels: both panels' inner margins plus the item spacing
+const WIDE_PANEL_WIDTH: f32 = PANEL_WIDTH * 2.0 + 16.0;
 const KNOB_SIZE: f32 = 42.0;
+const MACRO_KNOB_SIZE: f32 = 72.0;
 const KNOB_FRAMES: usize = 128;
 const SIDEWOOD_WIDTH: f32 = 40.0;
 
@@ -182,27 +646,525 @@ static KNOB_PNG: &[u8] = include_bytes!("../assets/knob.png");
 static SIDEWOOD_PNG: &[u8] = include_bytes!("../assets/sidewood.png");
 static LOGO_PNG: &[u8] = include_bytes!("../assets/logo.png");
 
+// Per-channel MPE controls: bend -1..1, pressure 0..1, timbre (CC74) 0..1
+#[derive(Clone, Copy)]
+struct MpeChannel { bend: f32, pressure: f32, timbre: f32 }
+
+impl Default for MpeChannel { fn default() -> Self { Self { bend: 0.0, pressure: 0.0, timbre: 0.5 } } }
+
 #[derive(Clone, Copy)]
 struct Voice {
-    active: bool, note: u8, velocity: f32, sub_phase: f32,
+    active: bool, note: u8, velocity: f32, accent: bool, sub_phase: f32,
     unison_phases: [f32; 8], env_stage: u8, env_value: f32, env_time: f32, 
     atk_start: f32, rel_start: f32,
     filter_lp: f32, filter_bp: f32, filter_lp2: f32, filter_bp2: f32,
+    ladder: [f32; 4], diode: [f32; 3], diode_hp: f32,
+    f2_lp: f32, f2_bp: f32,
+    formant: [[f32; 2]; 3],
+    pitch_env: f32,
+    // Poly aftertouch (0..1); held only by the sustain pedal once the key is up
+    pressure: f32, sustained: bool,
+    // Host voice ID and note expressions (tuning in semitones, linear gain, pan -1..1, brightness 0..1)
+    voice_id: i32, channel: u8,
+    poly_mod: [f32; POLY_MOD_COUNT],
+    tuning: f32, expr_gain: f32, pan: f32, brightness: f32,
+    // Smoothed MPE channel bend in semitones
+    mpe_bend: f32,
+    // Constant-time glide speed, set on the first sample of each glide
+    glide_step: f32,
     target_note: f32, current_note: f32,
+    // Index 1 is the oscillator lane, only upsampled in split routing
+    os_a: [Halfband; 2], os_b: [Halfband; 2], os_comp: [f32; 2],
 }
 
 impl Default for Voice {
     fn default() -> Self {
         Self {
-            active: false, note: 0, velocity: 0.0, sub_phase: 0.0,
+            active: false, note: 0, velocity: 0.0, accent: false, sub_phase: 0.0,
             unison_phases: [0.0; 8], env_stage: 0, env_value: 0.0, env_time: 0.0, 
             atk_start: 0.0, rel_start: 0.0,
             filter_lp: 0.0, filter_bp: 0.0, filter_lp2: 0.0, filter_bp2: 0.0,
+            ladder: [0.0; 4], diode: [0.0; 3], diode_hp: 0.0,
+            f2_lp: 0.0, f2_bp: 0.0,
+            formant: [[0.0; 2]; 3],
+            pitch_env: 0.0,
+            pressure: 0.0, sustained: false,
+            voice_id: 0, channel: 0,
+            poly_mod: [0.0; POLY_MOD_COUNT],
+            tuning: 0.0, expr_gain: 1.0, pan: 0.0, brightness: 0.5,
+            mpe_bend: 0.0,
+            glide_step: 0.0,
             target_note: 69.0, current_note: 69.0,
+            os_a: [Halfband::new(&HB_TAPS_A); 2], os_b: [Halfband::new(&HB_TAPS_B); 2], os_comp: [0.0; 2],
+        }
+    }
+}
+
+// Settings for the per-voice drive + filter section, read once per block
+#[derive(Clone, Copy)]
+struct ToneSettings {
+    drive: f32, drive_type: i32, flt_model: i32, flt_type: i32, flt_slope: i32, flt_res: f32,
+    flt_routing: i32, flt2_type: i32, flt2_res: f32,
+    vowel: f32,
+    // Comb modes: loop pitch in Hz and damping coefficient at the processing rate
+    comb_freq: f32, comb_damp: f32,
+}
+
+impl Voice {
+    // Mod sources of this voice on top of the global ones; key is 0 at middle C and 1 five octaves up
+    fn mod_sources(&self, global: &ModSources) -> ModSources {
+        // current_note sits an octave below the played key
+        let key = (self.current_note + 12.0 - 60.0) / 60.0;
+        ModSources { amp_env: self.env_value, pitch_env: self.pitch_env, velocity: self.velocity, key, poly_at: self.pressure, ..*global }
+    }
+
+    // Portamento: porta time per semitone, for the whole glide, or as an exponential approach
+    fn glide(&mut self, porta_time: f32, glide_type: i32, glide_coef: f32, sr: f32) {
+        if porta_time <= 0.001 { self.current_note = self.target_note; return; }
+        let diff = self.target_note - self.current_note;
+        if diff.abs() <= 0.01 { self.current_note = self.target_note; self.glide_step = 0.0; }
+        else if glide_type == GLIDE_EXP { self.current_note += diff * glide_coef; }
+        else {
+            if glide_type == GLIDE_TIME && self.glide_step == 0.0 { self.glide_step = diff.abs() / (porta_time * sr); }
+            let step = if glide_type == GLIDE_TIME { self.glide_step } else { 1.0 / (porta_time * sr) };
+            self.current_note += diff.signum() * step.min(diff.abs());
+        }
+    }
+
+    // Drive then both filters in the selected routing; input[1] carries the oscillators in split routing
+    // Parallel and split mix the two filters at half level each
+    fn drive_filter(&mut self, input: [f32; 2], g: [f32; 2], rate: f32, s: &ToneSettings, comb: &mut CombFilter) -> f32 {
+        let driven = apply_drive(input[0], s.drive, s.drive_type);
+        match s.flt_routing {
+            FILTER_ROUTE_SERIAL => { let y = self.filter1(driven, g[0], rate, s, comb); self.filter2(y, g[1], s) }
+            FILTER_ROUTE_PARALLEL => (self.filter1(driven, g[0], rate, s, comb) + self.filter2(driven, g[1], s)) * 0.5,
+            FILTER_ROUTE_SPLIT => {
+                let driven_osc = apply_drive(input[1], s.drive, s.drive_type);
+                (self.filter1(driven, g[0], rate, s, comb) + self.filter2(driven_osc, g[1], s)) * 0.5
+            }
+            _ => self.filter1(driven, g[0], rate, s, comb),
+        }
+    }
+
+    fn filter1(&mut self, driven: f32, g: f32, rate: f32, s: &ToneSettings, comb: &mut CombFilter) -> f32 {
+        // Comb modes: delay tuned to the note, cutoff damps the loop
+        match s.flt_type {
+            FILTER_COMB_POS | FILTER_COMB_NEG => {
+                let fb = if s.flt_type == FILTER_COMB_NEG { -s.flt_res } else { s.flt_res };
+                return comb.process(driven, rate / s.comb_freq, fb, s.comb_damp);
+            }
+            FILTER_VOWEL => return self.formant(driven, rate, s),
+            _ => {}
+        }
+        match s.flt_model {
+            1 => self.ladder(driven, g, s),
+            2 => self.diode(driven, g, rate, s),
+            _ => self.svf(driven, g, s),
+        }
+    }
+
+    fn svf(&mut self, driven: f32, g: f32, s: &ToneSettings) -> f32 {
+        let k = 2.0 - 2.0 * s.flt_res.min(0.98);
+
+        // Clamp filter state to prevent blowup
+        self.filter_lp = self.filter_lp.clamp(-10.0, 10.0);
+        self.filter_bp = self.filter_bp.clamp(-10.0, 10.0);
+
+        let hp = (driven - self.filter_lp - k * self.filter_bp) / (1.0 + k * g + g * g);
+        let bp = g * hp + self.filter_bp;
+        let lp = g * bp + self.filter_lp;
+        self.filter_bp = (bp + g * hp).clamp(-10.0, 10.0);
+        self.filter_lp = (lp + g * bp).clamp(-10.0, 10.0);
+
+        let mut flt_out = match s.flt_type { 0 => lp, 1 => hp, 3 => lp + hp, 4 => lp - hp, _ => bp };
+
+        // 24dB mode
+        if s.flt_slope == 1 {
+            self.filter_lp2 = self.filter_lp2.clamp(-10.0, 10.0);
+            self.filter_bp2 = self.filter_bp2.clamp(-10.0, 10.0);
+
+            let hp2 = (flt_out - self.filter_lp2 - k * self.filter_bp2) / (1.0 + k * g + g * g);
+            let bp2 = g * hp2 + self.filter_bp2;
+            let lp2 = g * bp2 + self.filter_lp2;
+            self.filter_bp2 = (bp2 + g * hp2).clamp(-10.0, 10.0);
+            self.filter_lp2 = (lp2 + g * bp2).clamp(-10.0, 10.0);
+            flt_out = match s.flt_type { 0 => lp2, 1 => hp2, 3 => lp2 + hp2, 4 => lp2 - hp2, _ => bp2 };
+        }
+
+        // Safety clamp output
+        flt_out.clamp(-10.0, 10.0)
+    }
+
+    // Three parallel bandpasses at the vowel formants; resonance narrows the bands
+    fn formant(&mut self, input: f32, rate: f32, s: &ToneSettings) -> f32 {
+        let pos = s.vowel.clamp(0.0, 1.0) * 4.0;
+        let i = (pos as usize).min(3);
+        let t = pos - i as f32;
+        let k = 0.4 - 0.35 * s.flt_res.min(0.99);
+        let mut out = 0.0;
+        for (band, st) in self.formant.iter_mut().enumerate() {
+            let (f0, a0) = VOWEL_FORMANTS[i][band];
+            let (f1, a1) = VOWEL_FORMANTS[i + 1][band];
+            let g = (std::f32::consts::PI * (f0 + (f1 - f0) * t) / rate).tan().min(1.0);
+            let hp = (input - st[0] - k * st[1]) / (1.0 + k * g + g * g);
+            let bp = g * hp + st[1];
+            let lp = g * bp + st[0];
+            st[1] = (bp + g * hp).clamp(-10.0, 10.0);
+            st[0] = (lp + g * bp).clamp(-10.0, 10.0);
+            // k * bp has unity gain at the centre regardless of bandwidth
+            out += k * bp * (a0 + (a1 - a0) * t);
+        }
+        (out * 1.5).clamp(-10.0, 10.0)
+    }
+
+    // Second filter: a plain 12dB SVF with its own type, cutoff and resonance
+    fn filter2(&mut self, input: f32, g: f32, s: &ToneSettings) -> f32 {
+        let k = 2.0 - 2.0 * s.flt2_res.min(0.98);
+        let hp = (input - self.f2_lp - k * self.f2_bp) / (1.0 + k * g + g * g);
+        let bp = g * hp + self.f2_bp;
+        let lp = g * bp + self.f2_lp;
+        self.f2_bp = (bp + g * hp).clamp(-10.0, 10.0);
+        self.f2_lp = (lp + g * bp).clamp(-10.0, 10.0);
+        let flt_out = match s.flt2_type { 1 => hp, 2 => bp, 3 => lp + hp, 4 => lp - hp, _ => lp };
+        flt_out.clamp(-10.0, 10.0)
+    }
+
+    // Moog-style 4-pole ladder with tanh input saturation; self-oscillates at high resonance
+    fn ladder(&mut self, driven: f32, g: f32, s: &ToneSettings) -> f32 {
+        let gg = g / (1.0 + g);
+        let k = s.flt_res * 4.2;
+        // Make up some of the passband level the feedback takes away
+        let x = ((driven - k * self.ladder[3]) * (1.0 + k * 0.25)).tanh();
+
+        let mut y = [0.0f32; 4];
+        let mut stage_in = x;
+        for (i, st) in self.ladder.iter_mut().enumerate() {
+            let v = (stage_in - *st) * gg;
+            y[i] = v + *st;
+            *st = (y[i] + v).clamp(-10.0, 10.0);
+            stage_in = y[i].tanh();
+        }
+
+        let (lp, hp, bp) = if s.flt_slope == 0 {
+            (y[1], x - 2.0 * y[0] + y[1], 2.0 * (y[0] - y[1]))
+        } else {
+            (y[3], x - 4.0 * y[0] + 6.0 * y[1] - 4.0 * y[2] + y[3], 4.0 * (y[1] - 2.0 * y[2] + y[3]))
+        };
+        let flt_out = match s.flt_type { 1 => hp, 2 => bp, 3 => x - bp, 4 => lp - hp, _ => lp };
+        flt_out.clamp(-10.0, 10.0)
+    }
+
+    // TB-303-style diode ladder: 3 poles, asymmetric saturation, highpassed resonance feedback
+    fn diode(&mut self, driven: f32, g: f32, rate: f32, s: &ToneSettings) -> f32 {
+        let gg = g / (1.0 + g);
+        let k = s.flt_res * 7.5;
+
+        let hp_coef = 1.0 - (-std::f32::consts::TAU * 150.0 / rate).exp();
+        self.diode_hp += hp_coef * (self.diode[2] - self.diode_hp);
+        let fb = self.diode[2] - self.diode_hp;
+
+        let x = driven - k * fb;
+        let mut stage_in = if x > 0.0 { x.tanh() } else { x / (1.0 - 0.5 * x) };
+        let mut y = [0.0f32; 3];
+        for (i, st) in self.diode.iter_mut().enumerate() {
+            let v = (stage_in - *st) * gg;
+            y[i] = v + *st;
+            *st = (y[i] + v).clamp(-10.0, 10.0);
+            stage_in = y[i];
+        }
+
+        let (lp, hp, bp) = (y[2] * (1.0 + k * 0.15), x - y[2], 2.0 * (y[0] - y[2]));
+        let flt_out = match s.flt_type { 1 => hp, 2 => bp, 3 => x - bp, 4 => lp - hp, _ => lp };
+        flt_out.clamp(-10.0, 10.0)
+    }
+
+    // Runs drive_filter at 1x, 2x or 4x the host rate (os_mode = oversampling param value)
+    fn tone_stage(&mut self, input: [f32; 2], cut: [f32; 2], sr: f32, os_mode: i32, s: &ToneSettings, comb: &mut CombFilter) -> f32 {
+        let factor = match os_mode { 1 => 2.0, 2 => 4.0, _ => 1.0 };
+        let rate = sr * factor;
+        let g = cut.map(|c| (std::f32::consts::PI * c / rate).tan().min(1.0));
+        let s = &ToneSettings { comb_damp: 1.0 - (-std::f32::consts::TAU * cut[0] / rate).exp(), ..*s };
+        let split = s.flt_routing == FILTER_ROUTE_SPLIT;
+        match os_mode {
+            1 => {
+                let up = self.os_a[0].upsample(input[0]);
+                let up_osc = if split { self.os_a[1].upsample(input[1]) } else { [0.0; 2] };
+                let y = [self.drive_filter([up[0], up_osc[0]], g, rate, s, comb), self.drive_filter([up[1], up_osc[1]], g, rate, s, comb)];
+                self.os_a[0].downsample(y)
+            }
+            2 => {
+                let up = self.os_a[0].upsample(input[0]);
+                let up_osc = if split { self.os_a[1].upsample(input[1]) } else { [0.0; 2] };
+                let mut mid = [0.0; 2];
+                for (i, m) in mid.iter_mut().enumerate() {
+                    // One sample at 2x keeps the total latency a whole number of host samples
+                    let delayed = self.os_comp;
+                    self.os_comp = [up[i], up_osc[i]];
+                    let up2 = self.os_b[0].upsample(delayed[0]);
+                    let up2_osc = if split { self.os_b[1].upsample(delayed[1]) } else { [0.0; 2] };
+                    let y = [self.drive_filter([up2[0], up2_osc[0]], g, rate, s, comb), self.drive_filter([up2[1], up2_osc[1]], g, rate, s, comb)];
+                    *m = self.os_b[0].downsample(y);
+                }
+                self.os_a[0].downsample(mid)
+            }
+            _ => self.drive_filter(input, g, sr, s, comb),
+        }
+    }
+}
+
+// ========== LFO ==========
+// Breakpoint of a drawn LFO shape; curve bends the segment starting here
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct LfoPoint { x: f32, y: f32, curve: f32 }
+
+// One drawn LFO cycle: len points sorted by x in 0..1, first at 0, last segment wraps
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct LfoShape { points: [LfoPoint; MAX_LFO_POINTS], len: usize }
+
+impl Default for LfoShape {
+    fn default() -> Self { Self::from_points(&[(0.0, 1.0, 0.0), (0.5, -1.0, 0.0)]) }
+}
+
+impl LfoShape {
+    fn from_points(points: &[(f32, f32, f32)]) -> Self {
+        let mut shape = Self { points: [LfoPoint::default(); MAX_LFO_POINTS], len: points.len().min(MAX_LFO_POINTS) };
+        for (dst, &(x, y, curve)) in shape.points.iter_mut().zip(points) { *dst = LfoPoint { x, y, curve }; }
+        shape
+    }
+
+    // End of the segment starting at point i
+    fn segment_end(&self, i: usize) -> (f32, f32) {
+        if i + 1 < self.len { (self.points[i + 1].x, self.points[i + 1].y) } else { (1.0, self.points[0].y) }
+    }
+
+    fn eval(&self, phase: f32) -> f32 {
+        if self.len == 0 { return 0.0; }
+        let i = self.points[1..self.len].iter().position(|p| phase < p.x).unwrap_or(self.len - 1);
+        let a = self.points[i];
+        let (bx, by) = self.segment_end(i);
+        if bx - a.x <= 0.0 { return by; }
+        a.y + (by - a.y) * env_curve((phase - a.x) / (bx - a.x), a.curve)
+    }
+
+    // Adds a point, keeping the list sorted. Returns its index
+    fn insert(&mut self, x: f32, y: f32) -> Option<usize> {
+        if self.len >= MAX_LFO_POINTS { return None; }
+        let i = self.points[1..self.len].iter().position(|p| x < p.x).map_or(self.len, |i| i + 1);
+        self.points.copy_within(i..self.len, i + 1);
+        self.points[i] = LfoPoint { x, y, curve: 0.0 };
+        self.len += 1;
+        Some(i)
+    }
+
+    fn remove(&mut self, i: usize) {
+        // The first point anchors the cycle start
+        if i == 0 || i >= self.len { return; }
+        self.points.copy_within(i + 1..self.len, i);
+        self.len -= 1;
+    }
+
+    // Restores the invariants above on shapes read back from saved state
+    fn sanitize(&mut self) {
+        self.len = self.len.clamp(1, MAX_LFO_POINTS);
+        for p in &mut self.points[..self.len] {
+            p.x = if p.x.is_finite() { p.x.clamp(0.0, 1.0) } else { 0.0 };
+            p.y = if p.y.is_finite() { p.y.clamp(-1.0, 1.0) } else { 0.0 };
+            p.curve = if p.curve.is_finite() { p.curve.clamp(-1.0, 1.0) } else { 0.0 };
+        }
+        self.points[..self.len].sort_by(|a, b| a.x.total_cmp(&b.x));
+        self.points[0].x = 0.0;
+    }
+}
+
+// Per-block snapshot of one LFO's parameters
+#[derive(Clone, Copy, Default)]
+struct LfoSettings { inc: f64, depth: f32, wave: i32, target: i32, mode: i32, start: f64, fade_step: f32 }
+
+// Running state of one global LFO with its own random source for S&H and smooth random
+// start_mod is the mod matrix offset on the start phase, applied from the next sample
+#[derive(Clone, Copy)]
+struct LfoState { phase: f64, fade: f32, rand_prev: f32, rand_next: f32, rng: u32, start_mod: f64 }
+
+impl LfoState {
+    fn new(seed: u32) -> Self { Self { phase: 0.0, fade: 1.0, rand_prev: 0.0, rand_next: 0.0, rng: seed, start_mod: 0.0 } }
+
+    fn next_random(&mut self) {
+        self.rng ^= self.rng << 13; self.rng ^= self.rng >> 17; self.rng ^= self.rng << 5;
+        self.rand_prev = self.rand_next;
+        self.rand_next = self.rng as f32 / u32::MAX as f32 * 2.0 - 1.0;
+    }
+
+    // Cycle position after the start phase offset
+    fn shifted(&self, s: &LfoSettings) -> f64 { (self.phase + s.start + self.start_mod).fract() }
+
+    fn value(&self, s: &LfoSettings, shape: &LfoShape) -> f32 {
+        match s.wave {
+            LFO_DRAWN => shape.eval(self.shifted(s) as f32),
+            LFO_SAMPLE_HOLD => self.rand_next,
+            LFO_SMOOTH_RANDOM => {
+                let t = self.shifted(s) as f32;
+                self.rand_prev + (self.rand_next - self.rand_prev) * t * t * (3.0 - 2.0 * t)
+            }
+            _ => gen_wave(self.shifted(s), s.wave),
+        }
+    }
+
+    fn advance(&mut self, s: &LfoSettings) {
+        self.fade = (self.fade + s.fade_step).min(1.0);
+        let before = self.shifted(s);
+        self.phase += s.inc;
+        if s.mode == LFO_ONE_SHOT { self.phase = self.phase.min(LFO_ONE_SHOT_END); }
+        else if self.phase >= 1.0 { self.phase -= 1.0; }
+        // The random shapes step when the shifted cycle wraps, so the start phase moves their steps too
+        if self.shifted(s) < before { self.next_random(); }
+    }
+
+    // Restarts the fade-in, and the cycle too when reset_phase is set
+    fn note_on(&mut self, reset_phase: bool) {
+        self.fade = 0.0;
+        if reset_phase { self.phase = 0.0; self.next_random(); }
+    }
+}
+
+// ========== TUNING ==========
+// Scala keyboard mapping (.kbm). No keys means the linear mapping
+#[derive(Clone, Serialize, Deserialize)]
+struct KeyMap { first: i32, last: i32, middle: i32, ref_note: i32, ref_freq: f64, period_degree: i64, keys: Vec<Option<i64>> }
+
+impl Default for KeyMap {
+    fn default() -> Self { Self { first: 0, last: 127, middle: 60, ref_note: 69, ref_freq: 440.0, period_degree: 0, keys: Vec::new() } }
+}
+
+// Largest key map pattern and scale degree a .kbm may use
+const KBM_MAX_SIZE: i64 = 128;
+const KBM_MAX_DEGREE: i64 = 1 << 16;
+
+impl KeyMap {
+    fn validate(&self) -> Result<(), String> {
+        let key = |n: i32, what: &str| if (0..128).contains(&n) { Ok(()) } else { Err(format!("{} {} out of range 0..127", what, n)) };
+        key(self.first, "first note")?;
+        key(self.last, "last note")?;
+        key(self.middle, "middle note")?;
+        key(self.ref_note, "reference note")?;
+        if !(self.ref_freq.is_finite() && self.ref_freq > 0.0) { return Err("bad reference frequency".to_string()); }
+        if self.keys.len() as i64 > KBM_MAX_SIZE { return Err(format!("map size {} out of range 0..{}", self.keys.len(), KBM_MAX_SIZE)); }
+        let degree_ok = |d: i64| (0..=KBM_MAX_DEGREE).contains(&d);
+        if !degree_ok(self.period_degree) { return Err(format!("octave degree {} out of range", self.period_degree)); }
+        if self.keys.iter().flatten().any(|&d| !degree_ok(d)) { return Err("map entry out of range".to_string()); }
+        Ok(())
+    }
+}
+
+// Scale loaded from Scala files, saved with the plugin state (empty = 12-TET)
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Tuning {
+    name: String,
+    // Degree 1..n in cents above the tonic; the last one is the period
+    cents: Vec<f64>,
+    map: KeyMap,
+}
+
+// Non-comment lines of a Scala file
+fn scala_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(|l| l.trim()).filter(|l| !l.starts_with('!'))
+}
+
+fn parse_scala_pitch(line: &str) -> Result<f64, String> {
+    let tok = line.split_whitespace().next().ok_or("empty pitch line")?;
+    if tok.contains('.') { return tok.parse::<f64>().ok().filter(|c| c.is_finite()).ok_or(format!("bad cents value '{}'", tok)); }
+    let (num, den) = tok.split_once('/').unwrap_or((tok, "1"));
+    match (num.parse::<f64>(), den.parse::<f64>()) {
+        (Ok(n), Ok(d)) if n > 0.0 && d > 0.0 => Ok(1200.0 * (n / d).log2()),
+        _ => Err(format!("bad ratio '{}'", tok)),
+    }
+}
+
+impl Tuning {
+    fn parse_scl(text: &str) -> Result<(String, Vec<f64>), String> {
+        let mut lines = scala_lines(text);
+        let name = lines.next().ok_or("missing description")?.to_string();
+        let count: usize = lines.next().and_then(|l| l.split_whitespace().next()?.parse().ok()).ok_or("missing note count")?;
+        let cents = lines.take(count).map(parse_scala_pitch).collect::<Result<Vec<_>, _>>()?;
+        if cents.len() != count || count == 0 { return Err(format!("expected {} pitches, found {}", count, cents.len())); }
+        Ok((name, cents))
+    }
+
+    fn parse_kbm(text: &str) -> Result<KeyMap, String> {
+        let mut lines = scala_lines(text).filter(|l| !l.is_empty());
+        let mut next = |what: &str| lines.next().and_then(|l| l.split_whitespace().next()).map(str::to_string).ok_or(format!("missing {}", what));
+        let int = |s: String| s.parse::<i64>().map_err(|_| format!("bad number '{}'", s));
+        let size = int(next("map size")?)?;
+        if !(0..=KBM_MAX_SIZE).contains(&size) { return Err(format!("map size {} out of range 0..{}", size, KBM_MAX_SIZE)); }
+        let note = |s: String| int(s).map(|n| n.clamp(-1, 128) as i32);
+        let first = note(next("first note")?)?;
+        let last = note(next("last note")?)?;
+        let middle = note(next("middle note")?)?;
+        let ref_note = note(next("reference note")?)?;
+        let ref_freq = next("reference frequency")?.parse::<f64>().map_err(|_| "bad reference frequency".to_string())?;
+        let period_degree = int(next("octave degree")?)?;
+        // "x" and missing trailing entries are unmapped keys
+        let keys = (0..size).map(|_| match next("") {
+            Ok(t) if t != "x" => int(t).map(Some),
+            _ => Ok(None),
+        }).collect::<Result<_, _>>()?;
+        let map = KeyMap { first, last, middle, ref_note, ref_freq, period_degree, keys };
+        map.validate()?;
+        Ok(map)
+    }
+
+    fn from_scala(scl: &str, kbm: Option<&str>) -> Result<Tuning, String> {
+        let (name, cents) = Self::parse_scl(scl)?;
+        let map = match kbm { Some(k) => Self::parse_kbm(k)?, None => KeyMap::default() };
+        Ok(Tuning { name, cents, map })
+    }
+
+    // Scale degree for a MIDI key, None when the key map leaves it out
+    fn degree(&self, key: i32) -> Option<i64> {
+        let m = &self.map;
+        let off = (key - m.middle) as i64;
+        if m.keys.is_empty() { return Some(off); }
+        let size = m.keys.len() as i64;
+        m.keys[off.rem_euclid(size) as usize].map(|d| off.div_euclid(size) * m.period_degree + d)
+    }
+
+    fn degree_cents(&self, degree: i64) -> f64 {
+        let n = self.cents.len() as i64;
+        let r = degree.rem_euclid(n);
+        degree.div_euclid(n) as f64 * self.cents[n as usize - 1] + if r == 0 { 0.0 } else { self.cents[r as usize - 1] }
+    }
+
+    // Saved state is checked like a loaded file so a bad one can't reach table()
+    fn sanitize(&mut self) {
+        if self.cents.iter().any(|c| !c.is_finite()) || self.map.validate().is_err() { *self = Tuning::default(); }
+    }
+
+    fn table(&self) -> TuningTable {
+        let mut t = TuningTable::default();
+        if self.cents.is_empty() { return t; }
+        let m = &self.map;
+        let ref_cents = self.degree_cents(self.degree(m.ref_note).unwrap_or((m.ref_note - m.middle) as i64));
+        for key in 0..128 {
+            match self.degree(key as i32) {
+                Some(d) if (m.first..=m.last).contains(&(key as i32)) => {
+                    let freq = m.ref_freq * 2.0_f64.powf((self.degree_cents(d) - ref_cents) / 1200.0);
+                    t.pitch[key] = (69.0 + 12.0 * (freq / 440.0).log2()) as f32;
+                }
+                _ => t.mapped[key] = false,
+            }
         }
+        t.span = if m.keys.is_empty() { self.cents.len() as i32 } else { m.keys.len() as i32 };
+        t
     }
 }
 
+// Audio-thread tuning: pitch of each key in 12-TET semitones and keys per period for the arp
+#[derive(Clone, Copy)]
+struct TuningTable { pitch: [f32; 128], mapped: [bool; 128], span: i32 }
+
+impl Default for TuningTable {
+    fn default() -> Self { Self { pitch: std::array::from_fn(|i| i as f32), mapped: [true; 128], span: 12 } }
+}
+
 // ========== EFFECTS ==========
 struct LowShelf { a0: f32, a1: f32, a2: f32, b1: f32, b2: f32, x1: f32, x2: f32, y1: f32, y2: f32 }
 impl LowShelf {
@@ -232,6 +1194,27 @@ impl LowShelf {
     }
 }
 
+// Per-voice comb for the comb filter modes, tuned to the note and damped by a one-pole
+struct CombFilter { buffer: Vec<f32>, index: usize, damp: f32 }
+impl CombFilter {
+    fn new() -> Self { Self { buffer: vec![0.0; MAX_COMB_SAMPLES], index: 0, damp: 0.0 } }
+    fn clear(&mut self) { self.buffer.fill(0.0); self.index = 0; self.damp = 0.0; }
+    fn process(&mut self, input: f32, delay: f32, feedback: f32, damp_coef: f32) -> f32 {
+        let len = self.buffer.len();
+        let d = delay.clamp(1.0, (len - 2) as f32);
+        let pos = self.index as f32 + len as f32 - d;
+        let i0 = pos as usize % len;
+        let i1 = (i0 + 1) % len;
+        let frac = pos.fract();
+        let delayed = self.buffer[i0] * (1.0 - frac) + self.buffer[i1] * frac;
+        self.damp += damp_coef * (delayed - self.damp);
+        let out = (input + feedback * self.damp).clamp(-10.0, 10.0);
+        self.buffer[self.index] = out;
+        self.index = (self.index + 1) % len;
+        out * (1.0 - 0.5 * feedback.abs())
+    }
+}
+
 struct SimpleReverb {
     comb_buffers: [Vec<f32>; 4], comb_indices: [usize; 4], comb_filters: [f32; 4],
     ap_buffers: [Vec<f32>; 2], ap_indices: [usize; 2],
@@ -271,16 +1254,193 @@ impl SimpleReverb {
     }
 }
 
+// ========== OVERSAMPLING ==========
+// Even-index taps of 31- and 15-tap Kaiser-windowed halfband lowpasses. The odd
+// taps are zero apart from the 0.5 center tap, which the polyphase code handles
+// as a plain delay
+const HB_TAPS_A: [f32; 16] = [
+    -0.000049628, 0.000642233, -0.002734351, 0.008020058, -0.019227, 0.041535325, -0.09122178, 0.31303513,
+    0.31303513, -0.09122178, 0.041535325, -0.019227, 0.008020058, -0.002734351, 0.000642233, -0.000049628,
+];
+const HB_TAPS_B: [f32; 8] = [-0.000675681, 0.012706253, -0.062679686, 0.3006491, 0.3006491, -0.062679686, 0.012706253, -0.000675681];
+const HB_MAX_TAPS: usize = 16;
+
+// Latency in host samples of each oversampling mode (2x: 15 + 15 at 2x, 4x: 7 + 7 at 4x plus 1)
+fn oversampling_latency(os_mode: i32) -> u32 {
+    match os_mode { 1 => 15, 2 => 19, _ => 0 }
+}
+
+// Polyphase 2x halfband up/downsampler with separate up and down histories
+#[derive(Clone, Copy)]
+struct Halfband {
+    taps: &'static [f32],
+    up_hist: [f32; HB_MAX_TAPS],
+    down_even: [f32; HB_MAX_TAPS],
+    down_odd: [f32; HB_MAX_TAPS],
+}
+impl Halfband {
+    fn new(taps: &'static [f32]) -> Self { Self { taps, up_hist: [0.0; HB_MAX_TAPS], down_even: [0.0; HB_MAX_TAPS], down_odd: [0.0; HB_MAX_TAPS] } }
+    fn upsample(&mut self, x: f32) -> [f32; 2] {
+        let n = self.taps.len();
+        self.up_hist.copy_within(0..n - 1, 1);
+        self.up_hist[0] = x;
+        let even: f32 = self.taps.iter().zip(&self.up_hist).map(|(t, h)| t * h).sum();
+        [2.0 * even, self.up_hist[n / 2 - 1]]
+    }
+    fn downsample(&mut self, y: [f32; 2]) -> f32 {
+        let n = self.taps.len();
+        self.down_even.copy_within(0..n - 1, 1);
+        self.down_odd.copy_within(0..n / 2, 1);
+        self.down_even[0] = y[0];
+        self.down_odd[0] = y[1];
+        let even: f32 = self.taps.iter().zip(&self.down_even).map(|(t, h)| t * h).sum();
+        even + 0.5 * self.down_odd[n / 2]
+    }
+}
+
+// ========== MIDI LEARN ==========
+// A CC bound to a parameter by its ID; the table is saved with the plugin state
+#[derive(Clone, Serialize, Deserialize)]
+struct MidiMapping { cc: u8, param_id: String }
+
+// Latest value of every CC, watched by the editor while learning
+struct MidiCcState { values: [AtomicU32; 128], dirty: [AtomicBool; 128] }
+
+impl Default for MidiCcState {
+    fn default() -> Self { Self { values: std::array::from_fn(|_| AtomicU32::new(0)), dirty: std::array::from_fn(|_| AtomicBool::new(false)) } }
+}
+
+impl MidiCcState {
+    fn store(&self, cc: u8, value: f32) {
+        let i = cc as usize & 127;
+        self.values[i].store(value.to_bits(), Ordering::Relaxed);
+        self.dirty[i].store(true, Ordering::Release);
+    }
+
+    fn take(&self, cc: u8) -> Option<f32> {
+        let i = cc as usize & 127;
+        if self.dirty[i].swap(false, Ordering::Acquire) { Some(f32::from_bits(self.values[i].load(Ordering::Relaxed))) } else { None }
+    }
+}
+
+// The mapping table resolved per CC. The audio thread only flags mapped CCs as pending;
+// the GUI thread then sets their parameters through the host, like a knob turn
+struct MidiCcMap { mapped: [AtomicBool; 128], pending: [AtomicBool; 128], queued: AtomicBool, params: Mutex<[Option<ParamPtr>; 128]> }
+
+impl Default for MidiCcMap {
+    fn default() -> Self {
+        Self { mapped: std::array::from_fn(|_| AtomicBool::new(false)), pending: std::array::from_fn(|_| AtomicBool::new(false)), queued: AtomicBool::new(false), params: Mutex::new([None; 128]) }
+    }
+}
+
+impl MidiCcMap {
+    // Audio thread: flags a mapped CC, true when no GUI task is queued yet
+    fn mark(&self, cc: u8) -> bool {
+        let i = cc as usize & 127;
+        if !self.mapped[i].load(Ordering::Relaxed) { return false; }
+        self.pending[i].store(true, Ordering::Release);
+        !self.queued.swap(true, Ordering::AcqRel)
+    }
+
+    // Only called when the table changes, never per frame
+    fn rebuild(&self, params: &BassParams, map: &[MidiMapping]) {
+        let pmap = params.param_map();
+        let mut ptrs = self.params.lock().unwrap();
+        *ptrs = [None; 128];
+        for m in map { ptrs[m.cc as usize & 127] = pmap.iter().find(|(id, _, _)| *id == m.param_id).map(|(_, ptr, _)| *ptr); }
+        for (flag, ptr) in self.mapped.iter().zip(ptrs.iter()) { flag.store(ptr.is_some(), Ordering::Relaxed); }
+    }
+
+    // Display name of the parameter a CC is bound to
+    fn param_name(&self, cc: u8) -> Option<String> {
+        // SAFETY: the pointer comes from this plugin's own param map
+        self.params.lock().unwrap()[cc as usize & 127].map(|ptr| unsafe { ptr.name() }.to_string())
+    }
+
+    // GUI thread: moves the parameter of every pending CC to the controller position
+    // Without a context yet the values stay pending until the next CC
+    fn apply(&self, values: &MidiCcState, context: Option<&dyn GuiContext>) {
+        self.queued.store(false, Ordering::Release);
+        let Some(context) = context else { return };
+        let ptrs = self.params.lock().unwrap();
+        for (i, ptr) in ptrs.iter().enumerate() {
+            let Some(ptr) = *ptr else { continue };
+            if !self.pending[i].swap(false, Ordering::Acquire) { continue; }
+            let v = f32::from_bits(values.values[i].load(Ordering::Relaxed));
+            // SAFETY: the pointer comes from this plugin's own param map
+            unsafe {
+                context.raw_begin_set_parameter(ptr);
+                context.raw_set_parameter_normalized(ptr, v);
+                context.raw_end_set_parameter(ptr);
+            }
+        }
+    }
+}
+
+// Work handed from the audio thread to the GUI thread
+pub enum BassTask { ApplyMidiCcs }
+
+// The egui editor plus a copy of the host's GuiContext, which learned CCs need to set parameters
+// The context stays valid after the window closes, so CCs keep working once the editor has been opened
+struct LearnEditor { inner: Box<dyn Editor>, context: Arc<Mutex<Option<Arc<dyn GuiContext>>>> }
+
+impl Editor for LearnEditor {
+    fn spawn(&self, parent: ParentWindowHandle, context: Arc<dyn GuiContext>) -> Box<dyn std::any::Any + Send> {
+        *self.context.lock().unwrap() = Some(context.clone());
+        self.inner.spawn(parent, context)
+    }
+    fn size(&self) -> (u32, u32) { self.inner.size() }
+    fn set_scale_factor(&self, factor: f32) -> bool { self.inner.set_scale_factor(factor) }
+    fn param_value_changed(&self, id: &str, normalized_value: f32) { self.inner.param_value_changed(id, normalized_value) }
+    fn param_modulation_changed(&self, id: &str, modulation_offset: f32) { self.inner.param_modulation_changed(id, modulation_offset) }
+    fn param_values_changed(&self) { self.inner.param_values_changed() }
+}
+
+// Knob context menu choice, handed from draw_knob to the editor through egui's temp data
+#[derive(Clone, Copy)]
+enum KnobMenu { Learn(ParamPtr), Clear(ParamPtr) }
+const KNOB_MENU_ID: &str = "knob_menu";
+const KNOB_LEARN_ID: &str = "knob_learn";
+
 // ========== EDITOR STATE ==========
-struct EditorState { new_preset_name: String, selected_category: PresetCategory }
-impl Default for EditorState { fn default() -> Self { Self { new_preset_name: String::new(), selected_category: PresetCategory::Init } } }
+#[derive(Clone, Copy, PartialEq)]
+enum EditorPage { Main, Macro, Filter, Env, Lfo, Mod, Tune }
+
+impl EditorPage {
+    fn name(&self) -> &'static str {
+        match self { Self::Main => "MAIN", Self::Macro => "MACRO", Self::Filter => "FILTER", Self::Env => "ENV", Self::Lfo => "LFO", Self::Mod => "MOD", Self::Tune => "TUNE" }
+    }
+    fn all() -> &'static [EditorPage] { &[Self::Main, Self::Macro, Self::Filter, Self::Env, Self::Lfo, Self::Mod, Self::Tune] }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ShapeDrag { Point(usize), Curve(usize) }
+
+struct EditorState { new_preset_name: String, selected_category: PresetCategory, page: EditorPage, shape_lfo: usize, shape_drag: Option<ShapeDrag>, midi_learn: Option<ParamPtr>, macro_sel: usize, scl_path: String, kbm_path: String, tuning_msg: String }
+impl Default for EditorState { fn default() -> Self { Self { new_preset_name: String::new(), selected_category: PresetCategory::Init, page: EditorPage::Main, shape_lfo: 0, shape_drag: None, midi_learn: None, macro_sel: 0, scl_path: String::new(), kbm_path: String::new(), tuning_msg: String::new() } } }
 
 // ========== PLUGIN ==========
 pub struct ArtcodeBass {
     params: Arc<BassParams>,
     sample_rate: f32,
     voices: [Voice; MAX_VOICES],
-    lfo_phase: f64,
+    combs: Vec<CombFilter>,
+    lfos: [LfoState; 3],
+    // Audio-thread copy of the drawn shapes, refreshed whenever the editor isn't holding the lock
+    lfo_shapes: [LfoShape; 3],
+    // Performance controllers (0..1) for the mod matrix
+    mod_wheel: f32,
+    aftertouch: f32,
+    // Pitch wheel position (-1..1) and the smoothed offset in semitones
+    pitch_bend: f32,
+    bend_semis: f32,
+    sustain: bool,
+    // Last voice started or moved by a note; it feeds voice sources to the global destinations
+    newest_voice: usize,
+    mpe: [MpeChannel; 16],
+    tuning: TuningTable,
+    // Set by the editor after loading a new tuning
+    tuning_dirty: Arc<AtomicBool>,
     last_note: f32,
     last_mono_note: u8,
     arp_notes: [u8; MAX_ARP_NOTES],
@@ -297,9 +1457,13 @@ pub struct ArtcodeBass {
     low_shelf_r: LowShelf,
     dc_filter_l: f32,
     dc_filter_r: f32,
+    os_mode: i32,
     current_preset: Arc<AtomicUsize>,
     presets: Arc<Mutex<Vec<PresetData>>>,
     editor_state: Arc<Mutex<EditorState>>,
+    midi_cc: Arc<MidiCcState>,
+    midi_targets: Arc<MidiCcMap>,
+    gui_context: Arc<Mutex<Option<Arc<dyn GuiContext>>>>,
 }
 
 impl Default for ArtcodeBass {
@@ -308,7 +1472,18 @@ impl Default for ArtcodeBass {
             params: Arc::new(BassParams::default()),
             sample_rate: 44100.0,
             voices: [Voice::default(); MAX_VOICES],
-            lfo_phase: 0.0,
+            combs: (0..MAX_VOICES).map(|_| CombFilter::new()).collect(),
+            lfos: [LfoState::new(0x1234_5678), LfoState::new(0x2468_ace1), LfoState::new(0x1357_9bdf)],
+            lfo_shapes: [LfoShape::default(); 3],
+            mod_wheel: 0.0,
+            aftertouch: 0.0,
+            pitch_bend: 0.0,
+            bend_semis: 0.0,
+            sustain: false,
+            newest_voice: 0,
+            mpe: [MpeChannel::default(); 16],
+            tuning: TuningTable::default(),
+            tuning_dirty: Arc::new(AtomicBool::new(false)),
             last_note: 69.0,
             last_mono_note: 0,
             arp_notes: [0; MAX_ARP_NOTES],
@@ -325,16 +1500,20 @@ impl Default for ArtcodeBass {
             low_shelf_r: LowShelf::new(),
             dc_filter_l: 0.0,
             dc_filter_r: 0.0,
+            os_mode: 0,
             current_preset: Arc::new(AtomicUsize::new(0)),
             presets: Arc::new(Mutex::new(create_factory_presets())),
             editor_state: Arc::new(Mutex::new(EditorState::default())),
+            midi_cc: Arc::new(MidiCcState::default()),
+            midi_targets: Arc::new(MidiCcMap::default()),
+            gui_context: Arc::new(Mutex::new(None)),
         }
     }
 }
 
 fn gen_wave(ph: f64, w: i32) -> f32 {
     let p = ph as f32;
-    match w { 0 => (p * std::f32::consts::TAU).sin(), 1 => 2.0 * p - 1.0, 2 => if p < 0.5 { 1.0 } else { -1.0 }, _ => 4.0 * (p - (p + 0.5).floor()).abs() - 1.0 }
+    match w { 0 => (p * std::f32::consts::TAU).sin(), 1 => 2.0 * p - 1.0, 2 => if p < 0.5 { 1.0 } else { -1.0 }, 4 => 1.0 - 2.0 * p, _ => 4.0 * (p - (p + 0.5).floor()).abs() - 1.0 }
 }
 
 fn apply_drive(sample: f32, drive: f32, drive_type: i32) -> f32 {
@@ -357,6 +1536,19 @@ fn arp_rate_to_seconds(rate_idx: i32, bpm: f32) -> f32 {
     beats_per_note * seconds_per_beat
 }
 
+const LFO_DIVISION_NAMES: [&str; 14] = ["1/1", "1/2", "1/2D", "1/2T", "1/4", "1/4D", "1/4T", "1/8", "1/8D", "1/8T", "1/16", "1/16D", "1/16T", "1/32"];
+
+fn lfo_division_beats(div_idx: i32) -> f64 {
+    // Length of one LFO cycle in quarter-note beats; dotted = 1.5x, triplet = 2/3x
+    match div_idx {
+        0 => 4.0, 1 => 2.0, 2 => 3.0, 3 => 4.0 / 3.0,
+        4 => 1.0, 5 => 1.5, 6 => 2.0 / 3.0,
+        7 => 0.5, 8 => 0.75, 9 => 1.0 / 3.0,
+        10 => 0.25, 11 => 0.375, 12 => 1.0 / 6.0,
+        _ => 0.125,
+    }
+}
+
 fn normalize_cutoff(v: f32) -> f32 { ((v / 20.0).ln() / (1000.0f32).ln()).clamp(0.0, 1.0) }
 fn normalize_attack(v: f32) -> f32 { ((v / 0.001).ln() / (5000.0f32).ln()).clamp(0.0, 1.0) }
 fn normalize_decay(v: f32) -> f32 { ((v / 0.001).ln() / (5000.0f32).ln()).clamp(0.0, 1.0) }
@@ -367,43 +1559,100 @@ fn normalize_detune(v: f32) -> f32 { (v + 100.0) / 200.0 }
 fn normalize_filter_env(v: f32) -> f32 { (v + 1.0) / 2.0 }
 fn normalize_delay_time(v: f32) -> f32 { (v - 0.05) / 0.95 }
 
+// Bends segment progress t (0..1): positive = exponential, negative = logarithmic
+fn env_curve(t: f32, curve: f32) -> f32 {
+    if curve.abs() < 0.001 { return t; }
+    let k = curve * 6.0;
+    (1.0 - (-k * t).exp()) / (1.0 - (-k).exp())
+}
+
+// One-pole coefficients for RC mode; the attack reaches 1.0 from silence in the stage time
+fn rc_attack_coef(dt: f32, time: f32) -> f32 { 1.0 - (-dt * (RC_ATTACK_TARGET / (RC_ATTACK_TARGET - 1.0)).ln() / time).exp() }
+fn rc_stage_coef(dt: f32, time: f32) -> f32 { 1.0 - (-dt * RC_STAGE_TCS / time).exp() }
+
+// Output level for a velocity. curve: 0 linear, 1 soft, 2 hard, 3 fixed; amount blends it in
+fn velocity_gain(velocity: f32, curve: i32, amount: f32) -> f32 {
+    let shaped = match curve { 1 => velocity.sqrt(), 2 => velocity * velocity, 3 => 1.0, _ => velocity };
+    1.0 - amount + amount * shaped
+}
+
+// Wheel position -1..1 to semitones, with separate up and down ranges
+fn bend_semitones(wheel: f32, up: i32, down: i32) -> f32 { wheel * (if wheel >= 0.0 { up } else { down }) as f32 }
+
+// Same scheme as the nih-plug examples, so hosts that track voices by channel and key still match
+fn fallback_voice_id(note: u8, channel: u8) -> i32 { note as i32 | ((channel as i32) << 16) }
+
 impl ArtcodeBass {
     fn simple_random(&mut self) -> u32 { self.rng_state ^= self.rng_state << 13; self.rng_state ^= self.rng_state >> 17; self.rng_state ^= self.rng_state << 5; self.rng_state }
 
-    fn note_on_voice(&mut self, note: u8, velocity: f32) {
-        // Find inactive voice (artcode_synth style)
-        let target_note = note as f32 - 12.0; // 1 octave down
-        let target_freq = 440.0 * 2.0_f32.powf((target_note - 69.0) / 12.0);
-        
-        for voice in &mut self.voices {
-            if !voice.active {
-                *voice = Voice {
-                    active: true, note, velocity, sub_phase: 0.0,
-                    unison_phases: [0.0; 8], env_stage: 1, env_value: 0.0, env_time: 0.0,
-                    atk_start: 0.0, rel_start: 0.0,
-                    filter_lp: 0.0, filter_bp: 0.0, filter_lp2: 0.0, filter_bp2: 0.0,
-                    target_note, current_note: self.last_note,
-                };
+    // Returns the voice this note stole or took over as (voice_id, channel, note), for VoiceTerminated
+    fn note_on_voice(&mut self, note: u8, velocity: f32, voice_id: Option<i32>, channel: u8) -> Option<(i32, u8, u8)> {
+        // Keys the tuning leaves unmapped stay silent
+        if note > 127 || !self.tuning.mapped[note as usize] { return None; }
+        let target_note = self.tuning.pitch[note as usize] - 12.0; // 1 octave down
+        // Notes without a host voice ID (and arp notes) get one derived from channel and key
+        let voice_id = voice_id.unwrap_or_else(|| fallback_voice_id(note, channel));
+        let retrig = self.params.env_retrigger.value();
+        let first_stage = if self.params.amp_delay.value() > 0.0 { ENV_DELAY } else { 1 };
+
+        // Legato: a held voice moves to the new note without touching its envelope
+        if retrig == RETRIG_LEGATO {
+            if let Some(i) = self.voices.iter().position(|v| v.active && v.env_stage != 4) {
+                let v = &mut self.voices[i];
+                let ended = (v.voice_id != voice_id).then_some((v.voice_id, v.channel, v.note));
+                v.note = note; v.velocity = velocity; v.target_note = target_note; v.glide_step = 0.0; v.sustained = false;
+                v.voice_id = voice_id; v.channel = channel; v.poly_mod = [0.0; POLY_MOD_COUNT];
+                self.newest_voice = i;
                 self.last_note = target_note;
                 self.last_mono_note = note;
-                return;
+                return ended;
             }
         }
-        // All voices active - steal voice 0
-        self.voices[0] = Voice {
-            active: true, note, velocity, sub_phase: 0.0,
-            unison_phases: [0.0; 8], env_stage: 1, env_value: 0.0, env_time: 0.0,
-            atk_start: 0.0, rel_start: 0.0,
-            filter_lp: 0.0, filter_bp: 0.0, filter_lp2: 0.0, filter_bp2: 0.0,
-            target_note, current_note: self.last_note,
+
+        // Retrigger and one-shot LFOs restart with every non-legato note; fade-ins always do
+        for (st, l) in self.lfos.iter_mut().zip(self.params.lfos()) { st.note_on(l.mode.value() != LFO_FREE); }
+
+        // Restart: a repeat of a still-sounding note attacks again from its current level
+        if retrig == RETRIG_RESTART {
+            if let Some(i) = self.voices.iter().position(|v| v.active && v.note == note) {
+                let v = &mut self.voices[i];
+                v.velocity = velocity; v.accent = velocity >= ACCENT_VELOCITY; v.sustained = false;
+                let ended = (v.voice_id != voice_id).then_some((v.voice_id, v.channel, v.note));
+                v.voice_id = voice_id; v.channel = channel; v.poly_mod = [0.0; POLY_MOD_COUNT];
+                v.env_stage = first_stage; v.env_time = 0.0; v.atk_start = v.env_value; v.pitch_env = 1.0;
+                self.newest_voice = i;
+                self.last_note = target_note;
+                self.last_mono_note = note;
+                return ended;
+            }
+        }
+
+        // Legato-only glide starts from the previous note only while a key is still held; pedal-held voices don't count
+        let held = self.voices.iter().any(|v| v.active && v.env_stage != 4 && !v.sustained);
+        let glide_from = if self.params.glide_legato.value() == 1 && !held { target_note } else { self.last_note };
+
+        // Find inactive voice (artcode_synth style), stealing voice 0 when all are active
+        let idx = self.voices.iter().position(|v| !v.active).unwrap_or(0);
+        let old = &self.voices[idx];
+        let ended = old.active.then_some((old.voice_id, old.channel, old.note));
+        self.voices[idx] = Voice {
+            active: true, note, velocity, accent: velocity >= ACCENT_VELOCITY, env_stage: first_stage, pitch_env: 1.0,
+            voice_id, channel, mpe_bend: self.mpe_bend_target(channel),
+            target_note, current_note: glide_from,
+            ..Voice::default()
         };
+        self.combs[idx].clear();
+        self.newest_voice = idx;
         self.last_note = target_note;
         self.last_mono_note = note;
+        ended
     }
 
-    fn note_off_voice(&mut self, note: u8) {
+    fn note_off_voice(&mut self, note: u8) { self.release_voices(|v| v.note == note); }
+
+    fn release_voices(&mut self, pick: impl Fn(&Voice) -> bool) {
         for v in &mut self.voices {
-            if v.active && v.note == note && v.env_stage != 4 {
+            if v.active && v.env_stage != 4 && pick(v) {
                 v.env_stage = 4;
                 v.rel_start = v.env_value;
                 v.env_time = 0.0;
@@ -411,7 +1660,32 @@ impl ArtcodeBass {
         }
     }
 
-    fn arp_add_note(&mut self, note: u8, vel: f32) {
+    // Voices a note expression applies to: the host voice ID when given, otherwise channel and key
+    fn expression_voices(&mut self, voice_id: Option<i32>, channel: u8, note: u8) -> impl Iterator<Item = &mut Voice> {
+        self.voices.iter_mut().filter(move |v| v.active && match voice_id { Some(id) => v.voice_id == id, None => v.channel == channel && v.note == note })
+    }
+
+    fn mpe_member(&self, channel: u8) -> bool { self.params.mpe_mode.value() == 1 && channel != MPE_MASTER_CHANNEL }
+
+    fn mpe_bend_target(&self, channel: u8) -> f32 {
+        if self.mpe_member(channel) { self.mpe[channel as usize & 15].bend * self.params.mpe_bend_range.value() as f32 } else { 0.0 }
+    }
+
+    fn send_terminated(ctx: &mut impl ProcessContext<Self>, timing: u32, ended: Option<(i32, u8, u8)>) {
+        if let Some((id, channel, note)) = ended { ctx.send_event(NoteEvent::VoiceTerminated { timing, voice_id: Some(id), channel, note }); }
+    }
+
+    // Key up while the pedal is down: the voice keeps sounding until the pedal lifts
+    fn sustain_voices(&mut self, pick: impl Fn(&Voice) -> bool) {
+        for v in &mut self.voices { if v.active && v.env_stage != 4 && pick(v) { v.sustained = true; } }
+    }
+
+    fn release_sustained(&mut self) {
+        self.release_voices(|v| v.sustained);
+        for v in &mut self.voices { v.sustained = false; }
+    }
+
+    fn arp_add_note(&mut self, note: u8, vel: f32) -> Option<(i32, u8, u8)> {
         if self.arp_note_count < MAX_ARP_NOTES {
             let was_empty = self.arp_note_count == 0;
             let mut ip = self.arp_note_count;
@@ -423,10 +1697,12 @@ impl ArtcodeBass {
             if was_empty {
                 self.arp_index = 0;
                 self.arp_timer = 0.0;
-                self.note_on_voice(note, vel);
+                let ended = self.note_on_voice(note, vel, None, 0);
                 self.arp_playing_note = Some(note);
+                return ended;
             }
         }
+        None
     }
 
     fn arp_remove_note(&mut self, note: u8) {
@@ -443,11 +1719,13 @@ impl ArtcodeBass {
     fn arp_next_note(&mut self, mode: i32, octaves: i32) -> Option<(u8, f32)> {
         if self.arp_note_count == 0 { return None; }
         let ts = self.arp_note_count * octaves as usize;
+        // One arp octave is one period of the tuning's key map
+        let span = self.tuning.span;
         match mode {
-            0 => { let ni = self.arp_index % self.arp_note_count; let o = (self.arp_index / self.arp_note_count) as i32; let n = self.arp_notes[ni].saturating_add((o * 12) as u8); let v = self.arp_velocities[ni]; self.arp_index = (self.arp_index + 1) % ts; Some((n, v)) }
-            1 => { let ri = ts - 1 - (self.arp_index % ts); let ni = ri % self.arp_note_count; let o = (ri / self.arp_note_count) as i32; let n = self.arp_notes[ni].saturating_add((o * 12) as u8); let v = self.arp_velocities[ni]; self.arp_index = (self.arp_index + 1) % ts; Some((n, v)) }
-            2 => { let cl = if ts > 1 { ts * 2 - 2 } else { 1 }; let pos = self.arp_index % cl; let ai = if pos < ts { pos } else { ts * 2 - 2 - pos }; let ni = ai % self.arp_note_count; let o = (ai / self.arp_note_count) as i32; let n = self.arp_notes[ni].saturating_add((o * 12) as u8); let v = self.arp_velocities[ni]; self.arp_index = (self.arp_index + 1) % cl; Some((n, v)) }
-            _ => { let i = (self.simple_random() as usize) % ts; let ni = i % self.arp_note_count; let o = (i / self.arp_note_count) as i32; let n = self.arp_notes[ni].saturating_add((o * 12) as u8); let v = self.arp_velocities[ni]; Some((n, v)) }
+            0 => { let ni = self.arp_index % self.arp_note_count; let o = (self.arp_index / self.arp_note_count) as i32; let n = (self.arp_notes[ni] as i32 + o * span).clamp(0, 127) as u8; let v = self.arp_velocities[ni]; self.arp_index = (self.arp_index + 1) % ts; Some((n, v)) }
+            1 => { let ri = ts - 1 - (self.arp_index % ts); let ni = ri % self.arp_note_count; let o = (ri / self.arp_note_count) as i32; let n = (self.arp_notes[ni] as i32 + o * span).clamp(0, 127) as u8; let v = self.arp_velocities[ni]; self.arp_index = (self.arp_index + 1) % ts; Some((n, v)) }
+            2 => { let cl = if ts > 1 { ts * 2 - 2 } else { 1 }; let pos = self.arp_index % cl; let ai = if pos < ts { pos } else { ts * 2 - 2 - pos }; let ni = ai % self.arp_note_count; let o = (ai / self.arp_note_count) as i32; let n = (self.arp_notes[ni] as i32 + o * span).clamp(0, 127) as u8; let v = self.arp_velocities[ni]; self.arp_index = (self.arp_index + 1) % cl; Some((n, v)) }
+            _ => { let i = (self.simple_random() as usize) % ts; let ni = i % self.arp_note_count; let o = (i / self.arp_note_count) as i32; let n = (self.arp_notes[ni] as i32 + o * span).clamp(0, 127) as u8; let v = self.arp_velocities[ni]; Some((n, v)) }
         }
     }
 }
@@ -461,11 +1739,20 @@ fn load_texture_from_bytes(ctx: &egui::Context, name: &str, bytes: &[u8]) -> egu
     ctx.load_texture(name, egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice()), egui::TextureOptions::LINEAR)
 }
 
-fn draw_knob(ui: &mut egui::Ui, value: f32, label: &str) -> Option<f32> {
-    let size = KNOB_SIZE;
-    let (rect, response) = ui.allocate_exact_size(egui::vec2(size, size + 14.0), egui::Sense::drag());
+fn draw_knob(ui: &mut egui::Ui, param: &impl Param, label: &str) -> Option<f32> { draw_knob_sized(ui, param, label, KNOB_SIZE) }
+
+fn draw_knob_sized(ui: &mut egui::Ui, param: &impl Param, label: &str, size: f32) -> Option<f32> {
+    let value = param.modulated_normalized_value();
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(size, size + 14.0), egui::Sense::click_and_drag());
     let mut nv = None;
     if response.dragged() { nv = Some((value - response.drag_delta().y * 0.005).clamp(0.0, 1.0)); }
+    response.context_menu(|ui| {
+        let mut pick = None;
+        if ui.button("MIDI Learn").clicked() { pick = Some(KnobMenu::Learn(param.as_ptr())); }
+        if ui.button("Clear MIDI").clicked() { pick = Some(KnobMenu::Clear(param.as_ptr())); }
+        if let Some(m) = pick { ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new(KNOB_MENU_ID), Some(m))); ui.close_menu(); }
+    });
+    let learning = ui.ctx().data(|d| d.get_temp::<Option<ParamPtr>>(egui::Id::new(KNOB_LEARN_ID))).flatten() == Some(param.as_ptr());
     if ui.is_rect_visible(rect) {
         let ctx = ui.ctx();
         let knob_tex = load_texture_from_bytes(ctx, "knob", KNOB_PNG);
@@ -475,7 +1762,9 @@ fn draw_knob(ui: &mut egui::Ui, value: f32, label: &str) -> Option<f32> {
         let uv_bottom = (frame_idx + 1) as f32 / KNOB_FRAMES as f32;
         let knob_rect = egui::Rect::from_min_size(rect.min, egui::vec2(size, size));
         p.image(knob_tex.id(), knob_rect, egui::Rect::from_min_max(egui::pos2(0.0, uv_top), egui::pos2(1.0, uv_bottom)), egui::Color32::WHITE);
-        p.text(egui::pos2(rect.center().x, rect.max.y), egui::Align2::CENTER_BOTTOM, label, egui::FontId::proportional(9.0), egui::Color32::from_rgb(180, 180, 190));
+        let label_color = if learning { egui::Color32::from_rgb(255, 100, 50) } else { egui::Color32::from_rgb(180, 180, 190) };
+        let font = if size > KNOB_SIZE { 11.0 } else { 9.0 };
+        p.text(egui::pos2(rect.center().x, rect.max.y), egui::Align2::CENTER_BOTTOM, label, egui::FontId::proportional(font), label_color);
     }
     nv
 }
@@ -499,6 +1788,178 @@ fn draw_toggle(ui: &mut egui::Ui, on: bool, label: &str) -> bool {
         .fill(if on { orange } else { egui::Color32::from_rgb(45, 45, 60) }).min_size(egui::vec2(32.0, 16.0))).clicked()
 }
 
+// Dropdown over names for an int param whose range starts at 0
+fn draw_mod_combo(ui: &mut egui::Ui, setter: &ParamSetter, param: &IntParam, names: &[&str], salt: (&str, usize)) {
+    let current = param.value();
+    egui::ComboBox::from_id_salt(salt).width(84.0).selected_text(egui::RichText::new(names[current as usize]).size(9.0)).show_ui(ui, |ui| {
+        for (i, name) in names.iter().enumerate() {
+            if ui.selectable_label(i as i32 == current, egui::RichText::new(*name).size(9.0)).clicked() { setter.set_parameter(param, i as i32); }
+        }
+    });
+}
+
+// One mod matrix row: source, destination, bipolar amount and via source
+fn draw_mod_slot(ui: &mut egui::Ui, setter: &ParamSetter, slot: &ModSlotParams, idx: usize) {
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(format!("{}", idx + 1)).size(9.0).color(egui::Color32::GRAY));
+        draw_mod_combo(ui, setter, &slot.source, &MOD_SOURCE_NAMES, ("mod_src", idx));
+        ui.label(egui::RichText::new("->").size(9.0).color(egui::Color32::GRAY));
+        draw_mod_combo(ui, setter, &slot.dest, &MOD_DEST_NAMES, ("mod_dst", idx));
+        let mut amt = slot.amount.value();
+        if ui.add(egui::Slider::new(&mut amt, -1.0..=1.0).fixed_decimals(2)).changed() { setter.set_parameter(&slot.amount, amt); }
+        ui.label(egui::RichText::new("via").size(9.0).color(egui::Color32::GRAY));
+        draw_mod_combo(ui, setter, &slot.via, &MOD_SOURCE_NAMES, ("mod_via", idx));
+    });
+}
+
+// One LFO's controls; the narrow main-page version leaves phase, fade and mode to the LFO page
+fn draw_lfo_panel(ui: &mut egui::Ui, setter: &ParamSetter, lfo: &LfoParams, title: &str, wide: bool, panel: egui::Color32, row_height: f32) {
+    let orange = egui::Color32::from_rgb(255, 100, 50);
+    let targets = ["Pit", "Flt", "Amp", "Vow"];
+    egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+        ui.set_min_width(if wide { WIDE_PANEL_WIDTH } else { PANEL_WIDTH }); ui.set_min_height(row_height);
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(title).size(9.0).color(orange));
+                ui.add_space(4.0);
+                if let Some(v) = draw_selector(ui, lfo.wave(), &["Sin", "Saw", "Sqr", "Tri", "SawD", "S&H", "Rnd", "Draw"]) {
+                    if v != LFO_DRAWN { setter.set_parameter_normalized(&lfo.waveform, v as f32 / (LFO_DRAWN - 1) as f32); }
+                    setter.set_parameter_normalized(&lfo.draw, if v == LFO_DRAWN { 1.0 } else { 0.0 });
+                }
+                if wide {
+                    ui.add_space(4.0);
+                    if let Some(v) = draw_selector(ui, lfo.target.value(), &targets) { setter.set_parameter_normalized(&lfo.target, v as f32 / 3.0); }
+                }
+            });
+            ui.add_space(2.0);
+            ui.horizontal(|ui| {
+                // Synced, the rate knob picks the note division instead
+                let sync = lfo.sync.value() == 1;
+                if sync {
+                    let div = lfo.division.value();
+                    if let Some(v) = draw_knob(ui, &lfo.division, LFO_DIVISION_NAMES[div as usize]) { setter.set_parameter_normalized(&lfo.division, v); }
+                } else if let Some(v) = draw_knob(ui, &lfo.rate, "Rate") { setter.set_parameter_normalized(&lfo.rate, v); }
+                if let Some(v) = draw_knob(ui, &lfo.depth, "Depth") { setter.set_parameter_normalized(&lfo.depth, v); }
+                if wide {
+                    if let Some(v) = draw_knob(ui, &lfo.start_phase, "Phase") { setter.set_parameter_normalized(&lfo.start_phase, v); }
+                    if let Some(v) = draw_knob(ui, &lfo.fade, "Fade") { setter.set_parameter_normalized(&lfo.fade, v); }
+                }
+                ui.add_space(8.0);
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("Sync").size(8.0).color(egui::Color32::GRAY));
+                        if draw_toggle(ui, sync, if sync { "ON" } else { "OFF" }) { setter.set_parameter_normalized(&lfo.sync, if sync { 0.0 } else { 1.0 }); }
+                    });
+                    if wide {
+                        if let Some(v) = draw_selector(ui, lfo.mode.value(), &["Free", "Retr", "1-Sh"]) { setter.set_parameter_normalized(&lfo.mode, v as f32 / 2.0); }
+                    } else if let Some(v) = draw_selector(ui, lfo.target.value(), &targets) { setter.set_parameter_normalized(&lfo.target, v as f32 / 3.0); }
+                });
+            });
+        });
+    });
+}
+
+const SHAPE_HANDLE_RADIUS: f32 = 6.0;
+
+fn shape_to_screen(rect: egui::Rect, x: f32, y: f32) -> egui::Pos2 {
+    egui::pos2(rect.left() + x * rect.width(), rect.center().y - y * rect.height() * 0.5)
+}
+
+// Handle under pos: breakpoints first, then the segment midpoints
+fn shape_hit(shape: &LfoShape, rect: egui::Rect, pos: egui::Pos2) -> Option<ShapeDrag> {
+    let near = |x: f32, y: f32| shape_to_screen(rect, x, y).distance(pos) <= SHAPE_HANDLE_RADIUS;
+    if let Some(i) = (0..shape.len).find(|&i| near(shape.points[i].x, shape.points[i].y)) { return Some(ShapeDrag::Point(i)); }
+    (0..shape.len).find(|&i| {
+        let mid = (shape.points[i].x + shape.segment_end(i).0) * 0.5;
+        near(mid, shape.eval(mid))
+    }).map(ShapeDrag::Curve)
+}
+
+// Breakpoint editor for a drawn LFO shape
+fn draw_shape_editor(ui: &mut egui::Ui, shape: &mut LfoShape, drag: &mut Option<ShapeDrag>, size: egui::Vec2) {
+    let orange = egui::Color32::from_rgb(255, 100, 50);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let pointer = response.interact_pointer_pos();
+
+    if response.drag_started() { *drag = pointer.and_then(|p| shape_hit(shape, rect, p)); }
+    if response.dragged() {
+        if let Some(pos) = pointer {
+            match *drag {
+                Some(ShapeDrag::Point(i)) => {
+                    let x = (pos.x - rect.left()) / rect.width();
+                    let lo = if i == 0 { 0.0 } else { shape.points[i - 1].x };
+                    let hi = if i == 0 { 0.0 } else { shape.segment_end(i).0 };
+                    shape.points[i].x = x.clamp(lo, hi);
+                    shape.points[i].y = ((rect.center().y - pos.y) / (rect.height() * 0.5)).clamp(-1.0, 1.0);
+                }
+                Some(ShapeDrag::Curve(i)) => {
+                    // Dragging up bulges the segment upwards whichever way it slopes
+                    let dir = if shape.segment_end(i).1 >= shape.points[i].y { 1.0 } else { -1.0 };
+                    shape.points[i].curve = (shape.points[i].curve - response.drag_delta().y * 0.02 * dir).clamp(-1.0, 1.0);
+                }
+                None => {}
+            }
+        }
+    }
+    if response.drag_stopped() { *drag = None; }
+    if let Some(pos) = pointer {
+        if response.clicked() && shape_hit(shape, rect, pos).is_none() {
+            shape.insert(((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0), ((rect.center().y - pos.y) / (rect.height() * 0.5)).clamp(-1.0, 1.0));
+        }
+        if response.secondary_clicked() {
+            match shape_hit(shape, rect, pos) {
+                Some(ShapeDrag::Point(i)) => shape.remove(i),
+                Some(ShapeDrag::Curve(i)) => shape.points[i].curve = 0.0,
+                None => {}
+            }
+        }
+    }
+
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(20, 21, 28));
+    painter.line_segment([egui::pos2(rect.left(), rect.center().y), egui::pos2(rect.right(), rect.center().y)], egui::Stroke::new(1.0, egui::Color32::from_rgb(45, 45, 60)));
+    let line: Vec<egui::Pos2> = (0..=128).map(|k| { let x = k as f32 / 128.0; shape_to_screen(rect, x, shape.eval(x.min(LFO_ONE_SHOT_END as f32))) }).collect();
+    painter.add(egui::Shape::line(line, egui::Stroke::new(1.5, orange)));
+    for i in 0..shape.len {
+        let mid = (shape.points[i].x + shape.segment_end(i).0) * 0.5;
+        painter.circle_stroke(shape_to_screen(rect, mid, shape.eval(mid)), 3.0, egui::Stroke::new(1.0, egui::Color32::GRAY));
+        painter.circle_filled(shape_to_screen(rect, shape.points[i].x, shape.points[i].y), 4.0, if *drag == Some(ShapeDrag::Point(i)) { egui::Color32::WHITE } else { orange });
+    }
+}
+
+fn param_id(params: &BassParams, ptr: ParamPtr) -> Option<String> {
+    params.param_map().into_iter().find(|(_, p, _)| *p == ptr).map(|(id, _, _)| id)
+}
+
+// Acts on knob menu picks and binds the first CC that moves while learning
+fn handle_midi_learn(ctx: &egui::Context, params: &BassParams, midi_cc: &MidiCcState, targets: &MidiCcMap, es: &mut EditorState) {
+    let mut map = params.midi_map.lock().unwrap();
+    match ctx.data_mut(|d| d.remove_temp::<Option<KnobMenu>>(egui::Id::new(KNOB_MENU_ID))).flatten() {
+        Some(KnobMenu::Learn(ptr)) => {
+            // Controllers moved before the request shouldn't bind
+            for cc in 0..128 { midi_cc.take(cc); }
+            es.midi_learn = Some(ptr);
+        }
+        Some(KnobMenu::Clear(ptr)) => {
+            if let Some(id) = param_id(params, ptr) { map.retain(|m| m.param_id != id); }
+            targets.rebuild(params, &map);
+        }
+        _ => {}
+    }
+
+    if let Some(ptr) = es.midi_learn {
+        ctx.request_repaint_after(std::time::Duration::from_millis(16));
+        if let Some(cc) = (0..128u8).find(|&cc| midi_cc.take(cc).is_some()) {
+            if let Some(id) = param_id(params, ptr) {
+                map.retain(|m| m.cc != cc && m.param_id != id);
+                map.push(MidiMapping { cc, param_id: id });
+                targets.rebuild(params, &map);
+            }
+            es.midi_learn = None;
+        }
+    }
+}
+
 // ========== PLUGIN IMPL ==========
 impl Plugin for ArtcodeBass {
     const NAME: &'static str = "artcode Bass";
@@ -507,20 +1968,30 @@ impl Plugin for ArtcodeBass {
     const EMAIL: &'static str = "";
     const VERSION: &'static str = env!("CARGO_PKG_VERSION");
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout { main_input_channels: None, main_output_channels: NonZeroU32::new(2), ..AudioIOLayout::const_default() }];
-    const MIDI_INPUT: MidiConfig = MidiConfig::Basic;
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = BassTask;
 
     fn params(&self) -> Arc<dyn Params> { self.params.clone() }
 
+    // Older sessions kept types past BP in a separate filter_mode param (mode n was type n + 2)
+    fn filter_state(state: &mut PluginState) {
+        if let Some(ParamValue::I32(mode)) = state.params.remove("filter_mode") {
+            if mode > 0 { state.params.insert("filter_type".into(), ParamValue::I32(mode + 2)); }
+        }
+    }
+
     fn editor(&mut self, _: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         let params = self.params.clone();
         let current_preset = self.current_preset.clone();
         let presets = self.presets.clone();
         let editor_state = self.editor_state.clone();
+        let midi_cc = self.midi_cc.clone();
+        let midi_targets = self.midi_targets.clone();
+        let tuning_dirty = self.tuning_dirty.clone();
 
-        create_egui_editor(self.params.editor_state.clone(), (), |_, _| {},
+        let inner = create_egui_editor(self.params.editor_state.clone(), (), |_, _| {},
             move |ctx, setter, _| {
                 let orange = egui::Color32::from_rgb(255, 100, 50);
                 let bg = egui::Color32::from_rgb(24, 25, 32);
@@ -529,6 +2000,8 @@ impl Plugin for ArtcodeBass {
 
                 // Load textures
                 let sidewood_tex = load_texture_from_bytes(ctx, "sidewood", SIDEWOOD_PNG);
+                let learn = editor_state.lock().unwrap().midi_learn;
+                ctx.data_mut(|d| d.insert_temp(egui::Id::new(KNOB_LEARN_ID), learn));
                 let logo_tex = load_texture_from_bytes(ctx, "logo", LOGO_PNG);
 
                 egui::CentralPanel::default().frame(egui::Frame::default().fill(bg)).show(ctx, |ui| {
@@ -599,8 +2072,30 @@ impl Plugin for ArtcodeBass {
                                             setter.set_parameter_normalized(&params.filter_cutoff, normalize_cutoff(p.filter_cut));
                                             setter.set_parameter_normalized(&params.filter_resonance, p.filter_res / 0.99);
                                             setter.set_parameter_normalized(&params.filter_env_amount, normalize_filter_env(p.filter_env));
-                                            setter.set_parameter_normalized(&params.filter_type, p.filter_type as f32 / 2.0);
+                                            setter.set_parameter_normalized(&params.filter_type, p.filter_type as f32 / FILTER_VOWEL as f32);
                                             setter.set_parameter_normalized(&params.filter_slope, p.filter_slope as f32);
+                                            setter.set_parameter_normalized(&params.filter_model, p.filter_model as f32 / 2.0);
+                                            setter.set_parameter_normalized(&params.accent, p.accent);
+                                            setter.set_parameter_normalized(&params.filter_keytrack, p.filter_key);
+                                            setter.set_parameter_normalized(&params.filter_velocity, p.filter_vel);
+                                            setter.set_parameter_normalized(&params.filter_key_center, p.key_center as f32 / 127.0);
+                                            setter.set_parameter_normalized(&params.filter2_type, p.filter2_type as f32 / 4.0);
+                                            setter.set_parameter_normalized(&params.filter2_cutoff, normalize_cutoff(p.filter2_cut));
+                                            setter.set_parameter_normalized(&params.filter2_resonance, p.filter2_res / 0.99);
+                                            setter.set_parameter_normalized(&params.filter_routing, p.filter_routing as f32 / 3.0);
+                                            setter.set_parameter_normalized(&params.vowel, p.vowel);
+                                            setter.set_parameter_normalized(&params.vowel_env, (p.vowel_env + 1.0) / 2.0);
+                                            setter.set_parameter_normalized(&params.amp_attack_curve, (p.atk_curve + 1.0) / 2.0);
+                                            setter.set_parameter_normalized(&params.amp_decay_curve, (p.dec_curve + 1.0) / 2.0);
+                                            setter.set_parameter_normalized(&params.amp_release_curve, (p.rel_curve + 1.0) / 2.0);
+                                            setter.set_parameter_normalized(&params.env_mode, p.env_mode as f32);
+                                            setter.set_parameter_normalized(&params.pitch_env_amount, (p.pitch_env + 48.0) / 96.0);
+                                            setter.set_parameter_normalized(&params.pitch_env_decay, params.pitch_env_decay.preview_normalized(p.pitch_decay));
+                                            setter.set_parameter_normalized(&params.velocity_amp, p.vel_amp);
+                                            setter.set_parameter_normalized(&params.velocity_curve, p.vel_curve as f32 / 3.0);
+                                            setter.set_parameter_normalized(&params.env_retrigger, p.retrigger as f32 / 2.0);
+                                            setter.set_parameter_normalized(&params.amp_delay, params.amp_delay.preview_normalized(p.env_delay));
+                                            setter.set_parameter_normalized(&params.amp_hold, params.amp_hold.preview_normalized(p.env_hold));
                                             setter.set_parameter_normalized(&params.drive, p.drive);
                                             setter.set_parameter_normalized(&params.drive_type, p.drive_type as f32 / 3.0);
                                             setter.set_parameter_normalized(&params.low_boost, p.low_boost);
@@ -608,11 +2103,17 @@ impl Plugin for ArtcodeBass {
                                             setter.set_parameter_normalized(&params.amp_decay, normalize_decay(p.decay));
                                             setter.set_parameter_normalized(&params.amp_sustain, p.sustain);
                                             setter.set_parameter_normalized(&params.amp_release, normalize_release(p.release));
-                                            setter.set_parameter_normalized(&params.lfo_rate, normalize_lfo_rate(p.lfo_rate));
-                                            setter.set_parameter_normalized(&params.lfo_depth, p.lfo_depth);
-                                            setter.set_parameter_normalized(&params.lfo_waveform, p.lfo_wave as f32 / 3.0);
-                                            setter.set_parameter_normalized(&params.lfo_target, p.lfo_target as f32 / 2.0);
+                                            params.lfo1.load_preset(setter, &p.lfo1());
+                                            params.lfo2.load_preset(setter, &p.lfo2);
+                                            params.lfo3.load_preset(setter, &p.lfo3);
+                                            *params.lfo_shapes.lock().unwrap() = p.lfo_shapes;
+                                            for (slot, m) in params.mod_slots.iter().zip(&p.mod_slots) { slot.load_slot(setter, m); }
                                             setter.set_parameter_normalized(&params.portamento, normalize_porta(p.porta));
+                                            setter.set_parameter(&params.bend_up, p.bend_up);
+                                            setter.set_parameter(&params.bend_down, p.bend_down);
+                                            setter.set_parameter(&params.glide_type, p.glide_type);
+                                            setter.set_parameter(&params.glide_legato, p.glide_legato);
+                                            for (m, mp) in params.macros.iter().zip(&p.macros) { m.load_preset(setter, mp); }
                                             setter.set_parameter_normalized(&params.delay_mix, p.delay_mix);
                                             setter.set_parameter_normalized(&params.delay_time, normalize_delay_time(p.delay_time));
                                             setter.set_parameter_normalized(&params.delay_feedback, p.delay_fb / 0.95);
@@ -635,9 +2136,24 @@ impl Plugin for ArtcodeBass {
                                         filter_type: params.filter_type.value(), filter_slope: params.filter_slope.value(),
                                         drive: params.drive.value(), drive_type: params.drive_type.value(), low_boost: params.low_boost.value(),
                                         attack: params.amp_attack.value(), decay: params.amp_decay.value(), sustain: params.amp_sustain.value(), release: params.amp_release.value(),
-                                        lfo_rate: params.lfo_rate.value(), lfo_depth: params.lfo_depth.value(), lfo_wave: params.lfo_waveform.value(), lfo_target: params.lfo_target.value(),
+                                        lfo_rate: params.lfo1.rate.value(), lfo_depth: params.lfo1.depth.value(), lfo_wave: params.lfo1.wave(), lfo_target: params.lfo1.target.value(),
                                         porta: params.portamento.value(), delay_mix: params.delay_mix.value(), delay_time: params.delay_time.value(), delay_fb: params.delay_feedback.value(),
                                         reverb_mix: params.reverb_mix.value(), reverb_size: params.reverb_size.value(),
+                                        filter_model: params.filter_model.value(), accent: params.accent.value(),
+                                        filter_key: params.filter_keytrack.value(), filter_vel: params.filter_velocity.value(), key_center: params.filter_key_center.value(),
+                                        filter2_type: params.filter2_type.value(), filter2_cut: params.filter2_cutoff.value(), filter2_res: params.filter2_resonance.value(),
+                                        filter_routing: params.filter_routing.value(), vowel: params.vowel.value(), vowel_env: params.vowel_env.value(),
+                                        atk_curve: params.amp_attack_curve.value(), dec_curve: params.amp_decay_curve.value(), rel_curve: params.amp_release_curve.value(),
+                                        env_mode: params.env_mode.value(), pitch_env: params.pitch_env_amount.value(), pitch_decay: params.pitch_env_decay.value(),
+                                        vel_amp: params.velocity_amp.value(), vel_curve: params.velocity_curve.value(), retrigger: params.env_retrigger.value(),
+                                        env_delay: params.amp_delay.value(), env_hold: params.amp_hold.value(),
+                                        lfo_sync: params.lfo1.sync.value(), lfo_div: params.lfo1.division.value(),
+                                        lfo_mode: params.lfo1.mode.value(), lfo_phase: params.lfo1.start_phase.value(), lfo_fade: params.lfo1.fade.value(),
+                                        lfo2: params.lfo2.to_preset(), lfo3: params.lfo3.to_preset(), lfo_shapes: *params.lfo_shapes.lock().unwrap(),
+                                        mod_slots: std::array::from_fn(|i| params.mod_slots[i].to_slot()),
+                                        bend_up: params.bend_up.value(), bend_down: params.bend_down.value(),
+                                        glide_type: params.glide_type.value(), glide_legato: params.glide_legato.value(),
+                                        macros: std::array::from_fn(|i| params.macros[i].to_preset()),
                                     };
                                     let mut presets_lock = presets.lock().unwrap();
                                     let new_idx = presets_lock.len();
@@ -653,187 +2169,548 @@ impl Plugin for ArtcodeBass {
 
                     ui.add_space(2.0);
 
-                    // Row 1: OSC1 + OSC2/SUB
-                    ui.horizontal(|ui| {
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.label(egui::RichText::new("OSC 1").size(9.0).color(orange));
-                                if let Some(v) = draw_selector(ui, params.osc1_waveform.value(), &["Sin", "Saw", "Sqr", "Tri"]) { setter.set_parameter_normalized(&params.osc1_waveform, v as f32 / 3.0); }
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.osc1_detune.modulated_normalized_value(), "Detune") { setter.set_parameter_normalized(&params.osc1_detune, v); }
-                                    if let Some(v) = draw_knob(ui, params.master_gain.modulated_normalized_value(), "Volume") { setter.set_parameter_normalized(&params.master_gain, v); }
+                    // Page tabs
+                    let mut page = editor_state.lock().unwrap().page;
+                    let page_names: Vec<&str> = EditorPage::all().iter().map(|p| p.name()).collect();
+                    let page_idx = EditorPage::all().iter().position(|p| *p == page).unwrap_or(0);
+                    if let Some(v) = draw_selector(ui, page_idx as i32, &page_names) {
+                        page = EditorPage::all()[v as usize];
+                        editor_state.lock().unwrap().page = page;
+                    }
+                    ui.add_space(2.0);
+
+                    if page == EditorPage::Main {
+                        // Row 1: OSC1 + OSC2/SUB
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("OSC 1").size(9.0).color(orange));
+                                    if let Some(v) = draw_selector(ui, params.osc1_waveform.value(), &["Sin", "Saw", "Sqr", "Tri"]) { setter.set_parameter_normalized(&params.osc1_waveform, v as f32 / 3.0); }
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.osc1_detune, "Detune") { setter.set_parameter_normalized(&params.osc1_detune, v); }
+                                        if let Some(v) = draw_knob(ui, &params.master_gain, "Volume") { setter.set_parameter_normalized(&params.master_gain, v); }
+                                    });
+                                });
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("OSC 2 / SUB").size(9.0).color(orange));
+                                    if let Some(v) = draw_selector(ui, params.osc2_waveform.value(), &["Sin", "Saw", "Sqr", "Tri"]) { setter.set_parameter_normalized(&params.osc2_waveform, v as f32 / 3.0); }
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.osc2_detune, "Detune") { setter.set_parameter_normalized(&params.osc2_detune, v); }
+                                        if let Some(v) = draw_knob(ui, &params.osc2_mix, "Mix") { setter.set_parameter_normalized(&params.osc2_mix, v); }
+                                        if let Some(v) = draw_knob(ui, &params.sub_volume, "Sub") { setter.set_parameter_normalized(&params.sub_volume, v); }
+                                    });
                                 });
                             });
                         });
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.label(egui::RichText::new("OSC 2 / SUB").size(9.0).color(orange));
-                                if let Some(v) = draw_selector(ui, params.osc2_waveform.value(), &["Sin", "Saw", "Sqr", "Tri"]) { setter.set_parameter_normalized(&params.osc2_waveform, v as f32 / 3.0); }
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.osc2_detune.modulated_normalized_value(), "Detune") { setter.set_parameter_normalized(&params.osc2_detune, v); }
-                                    if let Some(v) = draw_knob(ui, params.osc2_mix.modulated_normalized_value(), "Mix") { setter.set_parameter_normalized(&params.osc2_mix, v); }
-                                    if let Some(v) = draw_knob(ui, params.sub_volume.modulated_normalized_value(), "Sub") { setter.set_parameter_normalized(&params.sub_volume, v); }
+
+                        ui.add_space(2.0);
+
+                        // Row 2: UNISON + FILTER
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("UNISON").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_selector(ui, params.unison_voices.value() - 1, &["1", "2", "3", "4", "5", "6", "7", "8"]) { setter.set_parameter_normalized(&params.unison_voices, v as f32 / 7.0); }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.unison_spread, "Spread") { setter.set_parameter_normalized(&params.unison_spread, v); }
+                                        if let Some(v) = draw_knob(ui, &params.portamento, "Porta") { setter.set_parameter_normalized(&params.portamento, v); }
+                                    });
+                                });
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("FILTER").size(9.0).color(orange));
+                                        ui.add_space(4.0);
+                                        if let Some(v) = draw_selector(ui, params.filter_type.value(), &["LP", "HP", "BP", "Ntch", "Peak", "C+", "C-", "Vow"]) { setter.set_parameter_normalized(&params.filter_type, v as f32 / FILTER_VOWEL as f32); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.filter_cutoff, "Cutoff") { setter.set_parameter_normalized(&params.filter_cutoff, v); }
+                                        if let Some(v) = draw_knob(ui, &params.filter_resonance, "Reso") { setter.set_parameter_normalized(&params.filter_resonance, v); }
+                                        if let Some(v) = draw_knob(ui, &params.filter_env_amount, "Env") { setter.set_parameter_normalized(&params.filter_env_amount, v); }
+                                        if let Some(v) = draw_knob(ui, &params.accent, "Accent") { setter.set_parameter_normalized(&params.accent, v); }
+                                        ui.add_space(4.0);
+                                        ui.vertical(|ui| {
+                                            ui.label(egui::RichText::new("Model").size(8.0).color(egui::Color32::GRAY));
+                                            if let Some(v) = draw_selector(ui, params.filter_model.value(), &["SVF", "Ldr", "Diode"]) { setter.set_parameter_normalized(&params.filter_model, v as f32 / 2.0); }
+                                            if let Some(v) = draw_selector(ui, params.filter_slope.value(), &["12", "24"]) { setter.set_parameter_normalized(&params.filter_slope, v as f32); }
+                                        });
+                                    });
                                 });
                             });
                         });
-                    });
 
-                    ui.add_space(2.0);
+                        ui.add_space(2.0);
 
-                    // Row 2: UNISON + FILTER
-                    ui.horizontal(|ui| {
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.label(egui::RichText::new("UNISON").size(9.0).color(orange));
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_selector(ui, params.unison_voices.value() - 1, &["1", "2", "3", "4", "5", "6", "7", "8"]) { setter.set_parameter_normalized(&params.unison_voices, v as f32 / 7.0); }
+                        // Row 3: DRIVE + EFFECTS
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("DRIVE").size(9.0).color(orange));
+                                        ui.add_space(4.0);
+                                        if let Some(v) = draw_selector(ui, params.drive_type.value(), &["Soft", "Hard", "Tube", "Fuzz"]) { setter.set_parameter_normalized(&params.drive_type, v as f32 / 3.0); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.drive, "Drive") { setter.set_parameter_normalized(&params.drive, v); }
+                                        if let Some(v) = draw_knob(ui, &params.low_boost, "Low+") { setter.set_parameter_normalized(&params.low_boost, v); }
+                                        ui.add_space(8.0);
+                                        ui.vertical(|ui| {
+                                            ui.label(egui::RichText::new("Oversample").size(8.0).color(egui::Color32::GRAY));
+                                            if let Some(v) = draw_selector(ui, params.oversampling.value(), &["1x", "2x", "4x"]) { setter.set_parameter_normalized(&params.oversampling, v as f32 / 2.0); }
+                                        });
+                                    });
                                 });
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.unison_spread.modulated_normalized_value(), "Spread") { setter.set_parameter_normalized(&params.unison_spread, v); }
-                                    if let Some(v) = draw_knob(ui, params.portamento.modulated_normalized_value(), "Porta") { setter.set_parameter_normalized(&params.portamento, v); }
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("EFFECTS").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.delay_mix, "Dly") { setter.set_parameter_normalized(&params.delay_mix, v); }
+                                        if let Some(v) = draw_knob(ui, &params.delay_time, "Time") { setter.set_parameter_normalized(&params.delay_time, v); }
+                                        if let Some(v) = draw_knob(ui, &params.reverb_mix, "Rev") { setter.set_parameter_normalized(&params.reverb_mix, v); }
+                                        if let Some(v) = draw_knob(ui, &params.reverb_size, "Size") { setter.set_parameter_normalized(&params.reverb_size, v); }
+                                    });
                                 });
                             });
                         });
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("FILTER").size(9.0).color(orange));
-                                    ui.add_space(4.0);
-                                    if let Some(v) = draw_selector(ui, params.filter_type.value(), &["LP", "HP", "BP"]) { setter.set_parameter_normalized(&params.filter_type, v as f32 / 2.0); }
-                                    ui.add_space(4.0);
-                                    if let Some(v) = draw_selector(ui, params.filter_slope.value(), &["12", "24"]) { setter.set_parameter_normalized(&params.filter_slope, v as f32); }
+
+                        ui.add_space(2.0);
+
+                        // Row 4: ENVELOPE + LFO
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("ENVELOPE").size(9.0).color(orange));
+                                        ui.add_space(4.0);
+                                        if let Some(v) = draw_selector(ui, params.env_mode.value(), &["Curve", "RC"]) { setter.set_parameter_normalized(&params.env_mode, v as f32); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.amp_attack, "A") { setter.set_parameter_normalized(&params.amp_attack, v); }
+                                        if let Some(v) = draw_knob(ui, &params.amp_decay, "D") { setter.set_parameter_normalized(&params.amp_decay, v); }
+                                        if let Some(v) = draw_knob(ui, &params.amp_sustain, "S") { setter.set_parameter_normalized(&params.amp_sustain, v); }
+                                        if let Some(v) = draw_knob(ui, &params.amp_release, "R") { setter.set_parameter_normalized(&params.amp_release, v); }
+                                    });
                                 });
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.filter_cutoff.modulated_normalized_value(), "Cutoff") { setter.set_parameter_normalized(&params.filter_cutoff, v); }
-                                    if let Some(v) = draw_knob(ui, params.filter_resonance.modulated_normalized_value(), "Reso") { setter.set_parameter_normalized(&params.filter_resonance, v); }
-                                    if let Some(v) = draw_knob(ui, params.filter_env_amount.modulated_normalized_value(), "Env") { setter.set_parameter_normalized(&params.filter_env_amount, v); }
+                            });
+                            draw_lfo_panel(ui, setter, &params.lfo1, "LFO 1", false, panel, row_height);
+                        });
+
+                        ui.add_space(2.0);
+
+                        // Row 5: Arpeggiator
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH * 2.0 + 4.0);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("ARPEGGIATOR").size(9.0).color(orange));
+                                        ui.add_space(8.0);
+                                        let arp_on = params.arp_on.value() == 1;
+                                        if draw_toggle(ui, arp_on, if arp_on { "ON" } else { "OFF" }) { setter.set_parameter_normalized(&params.arp_on, if arp_on { 0.0 } else { 1.0 }); }
+                                    });
+                                    ui.add_space(4.0);
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("Mode").size(8.0).color(egui::Color32::GRAY));
+                                        if let Some(v) = draw_selector(ui, params.arp_mode.value(), &["Up", "Dn", "U/D", "Rnd"]) { setter.set_parameter_normalized(&params.arp_mode, v as f32 / 3.0); }
+                                        ui.add_space(10.0);
+                                        ui.label(egui::RichText::new("Speed").size(8.0).color(egui::Color32::GRAY));
+                                        if let Some(v) = draw_selector(ui, params.arp_rate.value(), &["1/4", "1/8", "1/16", "1/32"]) { setter.set_parameter_normalized(&params.arp_rate, v as f32 / 3.0); }
+                                        ui.add_space(10.0);
+                                        ui.label(egui::RichText::new("Oct").size(8.0).color(egui::Color32::GRAY));
+                                        if let Some(v) = draw_selector(ui, params.arp_octaves.value(), &["1", "2", "3", "4"]) { setter.set_parameter_normalized(&params.arp_octaves, v as f32 / 3.0); }
+                                    });
                                 });
                             });
                         });
-                    });
+                    } else if page == EditorPage::Macro {
+                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                            ui.set_min_width(WIDE_PANEL_WIDTH);
+                            ui.label(egui::RichText::new("MACROS").size(9.0).color(orange));
+                            ui.add_space(2.0);
+                            ui.horizontal(|ui| {
+                                for (i, m) in params.macros.iter().enumerate() {
+                                    ui.add_space(40.0);
+                                    if let Some(v) = draw_knob_sized(ui, &m.value, MACRO_NAMES[i], MACRO_KNOB_SIZE) { setter.set_parameter_normalized(&m.value, v); }
+                                    ui.add_space(24.0);
+                                }
+                            });
+                        });
 
-                    ui.add_space(2.0);
+                        ui.add_space(2.0);
 
-                    // Row 3: DRIVE + EFFECTS
-                    ui.horizontal(|ui| {
+                        // Routes of the selected macro
                         egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
+                            ui.set_min_width(WIDE_PANEL_WIDTH);
+                            let mut es = editor_state.lock().unwrap();
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("ROUTES").size(9.0).color(orange));
+                                ui.add_space(4.0);
+                                if let Some(v) = draw_selector(ui, es.macro_sel as i32, &MACRO_NAMES) { es.macro_sel = v as usize; }
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new("amount is how far the destination moves at full macro").size(8.0).color(egui::Color32::GRAY));
+                            });
+                            ui.add_space(2.0);
+                            let sel = es.macro_sel;
+                            for (r, route) in params.macros[sel].routes.iter().enumerate() {
                                 ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("DRIVE").size(9.0).color(orange));
-                                    ui.add_space(4.0);
-                                    if let Some(v) = draw_selector(ui, params.drive_type.value(), &["Soft", "Hard", "Tube", "Fuzz"]) { setter.set_parameter_normalized(&params.drive_type, v as f32 / 3.0); }
+                                    ui.label(egui::RichText::new(format!("{}", r + 1)).size(9.0).color(egui::Color32::GRAY));
+                                    draw_mod_combo(ui, setter, &route.dest, &MOD_DEST_NAMES, ("mac_dst", sel * MACRO_ROUTES + r));
+                                    let mut amt = route.amount.value();
+                                    if ui.add(egui::Slider::new(&mut amt, -1.0..=1.0).fixed_decimals(2)).changed() { setter.set_parameter(&route.amount, amt); }
                                 });
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.drive.modulated_normalized_value(), "Drive") { setter.set_parameter_normalized(&params.drive, v); }
-                                    if let Some(v) = draw_knob(ui, params.low_boost.modulated_normalized_value(), "Low+") { setter.set_parameter_normalized(&params.low_boost, v); }
+                            }
+                        });
+                    } else if page == EditorPage::Filter {
+                        // Row 1: KEY / VELOCITY, FILTER 2
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("CUTOFF TRACKING").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.filter_keytrack, "Key") { setter.set_parameter_normalized(&params.filter_keytrack, v); }
+                                        if let Some(v) = draw_knob(ui, &params.filter_velocity, "Vel") { setter.set_parameter_normalized(&params.filter_velocity, v); }
+                                        if let Some(v) = draw_knob(ui, &params.filter_key_center, "Center") { setter.set_parameter_normalized(&params.filter_key_center, v); }
+                                    });
+                                });
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("FILTER 2").size(9.0).color(orange));
+                                        ui.add_space(4.0);
+                                        if let Some(v) = draw_selector(ui, params.filter2_type.value(), &["LP", "HP", "BP", "Ntch", "Peak"]) { setter.set_parameter_normalized(&params.filter2_type, v as f32 / 4.0); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.filter2_cutoff, "Cutoff") { setter.set_parameter_normalized(&params.filter2_cutoff, v); }
+                                        if let Some(v) = draw_knob(ui, &params.filter2_resonance, "Reso") { setter.set_parameter_normalized(&params.filter2_resonance, v); }
+                                        ui.add_space(8.0);
+                                        ui.vertical(|ui| {
+                                            ui.label(egui::RichText::new("Routing").size(8.0).color(egui::Color32::GRAY));
+                                            if let Some(v) = draw_selector(ui, params.filter_routing.value(), &["Off", "Ser", "Par", "Split"]) { setter.set_parameter_normalized(&params.filter_routing, v as f32 / 3.0); }
+                                        });
+                                    });
                                 });
                             });
                         });
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.label(egui::RichText::new("EFFECTS").size(9.0).color(orange));
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.delay_mix.modulated_normalized_value(), "Dly") { setter.set_parameter_normalized(&params.delay_mix, v); }
-                                    if let Some(v) = draw_knob(ui, params.delay_time.modulated_normalized_value(), "Time") { setter.set_parameter_normalized(&params.delay_time, v); }
-                                    if let Some(v) = draw_knob(ui, params.reverb_mix.modulated_normalized_value(), "Rev") { setter.set_parameter_normalized(&params.reverb_mix, v); }
-                                    if let Some(v) = draw_knob(ui, params.reverb_size.modulated_normalized_value(), "Size") { setter.set_parameter_normalized(&params.reverb_size, v); }
+
+                        ui.add_space(2.0);
+
+                        // Row 2: FORMANT
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("FORMANT").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.vowel, "Vowel") { setter.set_parameter_normalized(&params.vowel, v); }
+                                        if let Some(v) = draw_knob(ui, &params.vowel_env, "Env") { setter.set_parameter_normalized(&params.vowel_env, v); }
+                                        ui.add_space(8.0);
+                                        ui.label(egui::RichText::new("A  E  I  O  U").size(8.0).color(egui::Color32::GRAY));
+                                    });
+                                });
+                            });
+                        });
+                    } else if page == EditorPage::Env {
+                        // Row 1: CURVES + PITCH ENV
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("CURVES").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.amp_attack_curve, "A") { setter.set_parameter_normalized(&params.amp_attack_curve, v); }
+                                        if let Some(v) = draw_knob(ui, &params.amp_decay_curve, "D") { setter.set_parameter_normalized(&params.amp_decay_curve, v); }
+                                        if let Some(v) = draw_knob(ui, &params.amp_release_curve, "R") { setter.set_parameter_normalized(&params.amp_release_curve, v); }
+                                    });
+                                });
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("PITCH ENV").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.pitch_env_amount, "Amt") { setter.set_parameter_normalized(&params.pitch_env_amount, v); }
+                                        if let Some(v) = draw_knob(ui, &params.pitch_env_decay, "Decay") { setter.set_parameter_normalized(&params.pitch_env_decay, v); }
+                                    });
                                 });
                             });
                         });
-                    });
 
-                    ui.add_space(2.0);
+                        ui.add_space(2.0);
 
-                    // Row 4: ENVELOPE + LFO
-                    ui.horizontal(|ui| {
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.label(egui::RichText::new("ENVELOPE").size(9.0).color(orange));
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.amp_attack.modulated_normalized_value(), "A") { setter.set_parameter_normalized(&params.amp_attack, v); }
-                                    if let Some(v) = draw_knob(ui, params.amp_decay.modulated_normalized_value(), "D") { setter.set_parameter_normalized(&params.amp_decay, v); }
-                                    if let Some(v) = draw_knob(ui, params.amp_sustain.modulated_normalized_value(), "S") { setter.set_parameter_normalized(&params.amp_sustain, v); }
-                                    if let Some(v) = draw_knob(ui, params.amp_release.modulated_normalized_value(), "R") { setter.set_parameter_normalized(&params.amp_release, v); }
+                        // Row 2: VELOCITY + RETRIGGER
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("VELOCITY").size(9.0).color(orange));
+                                        ui.add_space(4.0);
+                                        if let Some(v) = draw_selector(ui, params.velocity_curve.value(), &["Lin", "Soft", "Hard", "Fix"]) { setter.set_parameter_normalized(&params.velocity_curve, v as f32 / 3.0); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.velocity_amp, "Amp") { setter.set_parameter_normalized(&params.velocity_amp, v); }
+                                    });
+                                });
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("RETRIGGER").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    if let Some(v) = draw_selector(ui, params.env_retrigger.value(), &["Reset", "Restart", "Legato"]) { setter.set_parameter_normalized(&params.env_retrigger, v as f32 / 2.0); }
                                 });
                             });
                         });
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
-                            ui.vertical(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("LFO").size(9.0).color(orange));
-                                    ui.add_space(4.0);
-                                    if let Some(v) = draw_selector(ui, params.lfo_waveform.value(), &["Sin", "Saw", "Sqr", "Tri"]) { setter.set_parameter_normalized(&params.lfo_waveform, v as f32 / 3.0); }
-                                    ui.add_space(4.0);
-                                    if let Some(v) = draw_selector(ui, params.lfo_target.value(), &["Pit", "Flt", "Amp"]) { setter.set_parameter_normalized(&params.lfo_target, v as f32 / 2.0); }
+
+                        ui.add_space(2.0);
+
+                        // Row 3: DELAY / HOLD
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("DELAY / HOLD").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.amp_delay, "Dly") { setter.set_parameter_normalized(&params.amp_delay, v); }
+                                        if let Some(v) = draw_knob(ui, &params.amp_hold, "Hold") { setter.set_parameter_normalized(&params.amp_hold, v); }
+                                    });
                                 });
-                                ui.add_space(2.0);
-                                ui.horizontal(|ui| {
-                                    if let Some(v) = draw_knob(ui, params.lfo_rate.modulated_normalized_value(), "Rate") { setter.set_parameter_normalized(&params.lfo_rate, v); }
-                                    if let Some(v) = draw_knob(ui, params.lfo_depth.modulated_normalized_value(), "Depth") { setter.set_parameter_normalized(&params.lfo_depth, v); }
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.label(egui::RichText::new("PITCH BEND").size(9.0).color(orange));
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.bend_up, &format!("Up {}", params.bend_up.value())) { setter.set_parameter_normalized(&params.bend_up, v); }
+                                        if let Some(v) = draw_knob(ui, &params.bend_down, &format!("Down {}", params.bend_down.value())) { setter.set_parameter_normalized(&params.bend_down, v); }
+                                    });
                                 });
                             });
                         });
-                    });
 
-                    ui.add_space(2.0);
+                        ui.add_space(2.0);
 
-                    // Row 5: Arpeggiator
-                    ui.horizontal(|ui| {
-                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
-                            ui.set_min_width(PANEL_WIDTH * 2.0 + 4.0);
-                            ui.vertical(|ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("ARPEGGIATOR").size(9.0).color(orange));
-                                    ui.add_space(8.0);
-                                    let arp_on = params.arp_on.value() == 1;
-                                    if draw_toggle(ui, arp_on, if arp_on { "ON" } else { "OFF" }) { setter.set_parameter_normalized(&params.arp_on, if arp_on { 0.0 } else { 1.0 }); }
+                        // Row 4: MPE
+                        ui.horizontal(|ui| {
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("MPE").size(9.0).color(orange));
+                                        ui.add_space(8.0);
+                                        let mpe_on = params.mpe_mode.value() == 1;
+                                        if draw_toggle(ui, mpe_on, if mpe_on { "ON" } else { "OFF" }) { setter.set_parameter_normalized(&params.mpe_mode, if mpe_on { 0.0 } else { 1.0 }); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.mpe_bend_range, &format!("Bend {}", params.mpe_bend_range.value())) { setter.set_parameter_normalized(&params.mpe_bend_range, v); }
+                                        if let Some(v) = draw_knob(ui, &params.mpe_pressure_amp, "P>Amp") { setter.set_parameter_normalized(&params.mpe_pressure_amp, v); }
+                                        if let Some(v) = draw_knob(ui, &params.mpe_pressure_cutoff, "P>Cut") { setter.set_parameter_normalized(&params.mpe_pressure_cutoff, v); }
+                                        if let Some(v) = draw_knob(ui, &params.mpe_timbre_cutoff, "Tmb>Cut") { setter.set_parameter_normalized(&params.mpe_timbre_cutoff, v); }
+                                    });
                                 });
+                            });
+                            egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                                ui.set_min_width(PANEL_WIDTH); ui.set_min_height(row_height);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("GLIDE").size(9.0).color(orange));
+                                        ui.add_space(4.0);
+                                        if let Some(v) = draw_selector(ui, params.glide_type.value(), &["Rate", "Time", "Exp"]) { setter.set_parameter_normalized(&params.glide_type, v as f32 / 2.0); }
+                                        ui.add_space(4.0);
+                                        let legato = params.glide_legato.value() == 1;
+                                        if draw_toggle(ui, legato, "Legato") { setter.set_parameter_normalized(&params.glide_legato, if legato { 0.0 } else { 1.0 }); }
+                                    });
+                                    ui.add_space(2.0);
+                                    ui.horizontal(|ui| {
+                                        if let Some(v) = draw_knob(ui, &params.portamento, "Porta") { setter.set_parameter_normalized(&params.portamento, v); }
+                                    });
+                                });
+                            });
+                        });
+                    } else if page == EditorPage::Lfo {
+                        for (i, lfo) in params.lfos().into_iter().enumerate() {
+                            if i > 0 { ui.add_space(2.0); }
+                            draw_lfo_panel(ui, setter, lfo, ["LFO 1", "LFO 2", "LFO 3"][i], true, panel, row_height);
+                        }
+
+                        ui.add_space(2.0);
+
+                        // Drawn shape editor for the "Draw" waveform
+                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                            ui.set_min_width(WIDE_PANEL_WIDTH);
+                            let mut es = editor_state.lock().unwrap();
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("SHAPE").size(9.0).color(orange));
                                 ui.add_space(4.0);
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("Mode").size(8.0).color(egui::Color32::GRAY));
-                                    if let Some(v) = draw_selector(ui, params.arp_mode.value(), &["Up", "Dn", "U/D", "Rnd"]) { setter.set_parameter_normalized(&params.arp_mode, v as f32 / 3.0); }
-                                    ui.add_space(10.0);
-                                    ui.label(egui::RichText::new("Speed").size(8.0).color(egui::Color32::GRAY));
-                                    if let Some(v) = draw_selector(ui, params.arp_rate.value(), &["1/4", "1/8", "1/16", "1/32"]) { setter.set_parameter_normalized(&params.arp_rate, v as f32 / 3.0); }
-                                    ui.add_space(10.0);
-                                    ui.label(egui::RichText::new("Oct").size(8.0).color(egui::Color32::GRAY));
-                                    if let Some(v) = draw_selector(ui, params.arp_octaves.value(), &["1", "2", "3", "4"]) { setter.set_parameter_normalized(&params.arp_octaves, v as f32 / 3.0); }
+                                if let Some(v) = draw_selector(ui, es.shape_lfo as i32, &["LFO 1", "LFO 2", "LFO 3"]) { es.shape_lfo = v as usize; es.shape_drag = None; }
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new("click: add   right-click: remove   drag midpoint: curve").size(8.0).color(egui::Color32::GRAY));
+                            });
+                            ui.add_space(2.0);
+                            let shape_lfo = es.shape_lfo;
+                            let mut shapes = params.lfo_shapes.lock().unwrap();
+                            draw_shape_editor(ui, &mut shapes[shape_lfo], &mut es.shape_drag, egui::vec2(WIDE_PANEL_WIDTH, 100.0));
+                        });
+                    } else if page == EditorPage::Mod {
+                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                            ui.set_min_width(WIDE_PANEL_WIDTH);
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("MOD MATRIX").size(9.0).color(orange));
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new("amount is a share of the destination's range; envelopes, velocity and key reach LFO, FX and volume through the newest voice").size(8.0).color(egui::Color32::GRAY));
+                            });
+                            ui.add_space(2.0);
+                            for (i, slot) in params.mod_slots.iter().enumerate() { draw_mod_slot(ui, setter, slot, i); }
+                        });
+
+                        ui.add_space(2.0);
+
+                        // MIDI learn mappings
+                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                            ui.set_min_width(WIDE_PANEL_WIDTH);
+                            let mut es = editor_state.lock().unwrap();
+                            let mut map = params.midi_map.lock().unwrap();
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("MIDI LEARN").size(9.0).color(orange));
+                                ui.add_space(8.0);
+                                if es.midi_learn.is_some() {
+                                    ui.label(egui::RichText::new("move a controller...").size(8.0).color(orange));
+                                    if draw_toggle(ui, true, "Cancel") { es.midi_learn = None; }
+                                } else {
+                                    ui.label(egui::RichText::new("right-click a knob to learn").size(8.0).color(egui::Color32::GRAY));
+                                }
+                                if !map.is_empty() && draw_toggle(ui, false, "Clear All") { map.clear(); midi_targets.rebuild(&params, &map); }
+                            });
+                            ui.add_space(2.0);
+                            let mut remove = None;
+                            egui::ScrollArea::vertical().max_height(90.0).show(ui, |ui| {
+                                for (i, m) in map.iter().enumerate() {
+                                    let name = midi_targets.param_name(m.cc).unwrap_or_else(|| m.param_id.clone());
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new(format!("CC {:>3}", m.cc)).size(9.0).color(egui::Color32::from_rgb(180, 180, 190)));
+                                        ui.label(egui::RichText::new(name).size(9.0).color(egui::Color32::from_rgb(180, 180, 190)));
+                                        if draw_toggle(ui, false, "x") { remove = Some(i); }
+                                    });
+                                }
+                            });
+                            if let Some(i) = remove { map.remove(i); midi_targets.rebuild(&params, &map); }
+                        });
+                    } else if page == EditorPage::Tune {
+                        egui::Frame::default().fill(panel).corner_radius(4.0).inner_margin(6.0).show(ui, |ui| {
+                            ui.set_min_width(WIDE_PANEL_WIDTH);
+                            let mut es = editor_state.lock().unwrap();
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("TUNING").size(9.0).color(orange));
+                                ui.add_space(8.0);
+                                let name = params.tuning.lock().unwrap().name.clone();
+                                ui.label(egui::RichText::new(if name.is_empty() { "12-TET".to_string() } else { name }).size(9.0).color(egui::Color32::from_rgb(180, 180, 190)));
+                            });
+                            ui.add_space(4.0);
+                            ui.horizontal(|ui| {
+                                if let Some(v) = draw_knob(ui, &params.master_tune, &format!("A4 {:.1}", params.master_tune.value())) { setter.set_parameter_normalized(&params.master_tune, v); }
+                                ui.add_space(8.0);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new(".scl").size(9.0).color(egui::Color32::GRAY));
+                                        ui.add(egui::TextEdit::singleline(&mut es.scl_path).desired_width(360.0).hint_text("Scala scale file"));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new(".kbm").size(9.0).color(egui::Color32::GRAY));
+                                        ui.add(egui::TextEdit::singleline(&mut es.kbm_path).desired_width(360.0).hint_text("Keyboard mapping (optional)"));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        if draw_toggle(ui, false, "Load") {
+                                            let read = |path: &str| std::fs::read_to_string(path.trim()).map_err(|e| format!("{}: {}", path.trim(), e));
+                                            let kbm = if es.kbm_path.trim().is_empty() { Ok(None) } else { read(&es.kbm_path).map(Some) };
+                                            let loaded = read(&es.scl_path).and_then(|scl| Tuning::from_scala(&scl, kbm?.as_deref()));
+                                            match loaded {
+                                                Ok(t) => {
+                                                    es.tuning_msg = format!("Loaded {} notes", t.cents.len());
+                                                    *params.tuning.lock().unwrap() = t;
+                                                    tuning_dirty.store(true, Ordering::Release);
+                                                }
+                                                Err(e) => es.tuning_msg = e,
+                                            }
+                                        }
+                                        if draw_toggle(ui, false, "12-TET") {
+                                            *params.tuning.lock().unwrap() = Tuning::default();
+                                            tuning_dirty.store(true, Ordering::Release);
+                                            es.tuning_msg.clear();
+                                        }
+                                        ui.label(egui::RichText::new(&es.tuning_msg).size(8.0).color(egui::Color32::GRAY));
+                                    });
                                 });
                             });
                         });
-                    });
+                    }
                     }); // allocate_ui_at_rect
                 });
+
+                handle_midi_learn(ctx, &params, &midi_cc, &midi_targets, &mut editor_state.lock().unwrap());
             },
-        )
+        )?;
+        Some(Box::new(LearnEditor { inner, context: self.gui_context.clone() }))
     }
 
-    fn initialize(&mut self, _: &AudioIOLayout, cfg: &BufferConfig, _: &mut impl InitContext<Self>) -> bool {
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let (midi_cc, midi_targets, gui_context) = (self.midi_cc.clone(), self.midi_targets.clone(), self.gui_context.clone());
+        Box::new(move |task| match task {
+            BassTask::ApplyMidiCcs => midi_targets.apply(&midi_cc, gui_context.lock().unwrap().as_deref()),
+        })
+    }
+
+    fn initialize(&mut self, _: &AudioIOLayout, cfg: &BufferConfig, ctx: &mut impl InitContext<Self>) -> bool {
         self.sample_rate = cfg.sample_rate;
+        self.os_mode = self.params.oversampling.value();
+        ctx.set_latency_samples(oversampling_latency(self.os_mode));
         self.delay_buffer = vec![0.0; MAX_DELAY_SAMPLES];
         self.reverb = SimpleReverb::new(cfg.sample_rate);
         self.voices = [Voice::default(); MAX_VOICES];
+        let mut tuning = self.params.tuning.lock().unwrap();
+        tuning.sanitize();
+        self.tuning = tuning.table();
+        drop(tuning);
+        for shape in self.params.lfo_shapes.lock().unwrap().iter_mut() { shape.sanitize(); }
+        self.midi_targets.rebuild(&self.params, &self.params.midi_map.lock().unwrap());
         true
     }
 
     fn reset(&mut self) {
         self.voices = [Voice::default(); MAX_VOICES];
-        self.lfo_phase = 0.0;
+        for c in &mut self.combs { c.clear(); }
+        for l in &mut self.lfos { l.phase = 0.0; l.fade = 1.0; }
         self.last_note = 69.0;
         self.last_mono_note = 0;
         self.arp_notes = [0; MAX_ARP_NOTES];
@@ -846,6 +2723,13 @@ impl Plugin for ArtcodeBass {
         self.delay_index = 0;
         self.dc_filter_l = 0.0;
         self.dc_filter_r = 0.0;
+        self.pitch_bend = 0.0;
+        self.bend_semis = 0.0;
+        self.mod_wheel = 0.0;
+        self.aftertouch = 0.0;
+        self.sustain = false;
+        self.mpe = [MpeChannel::default(); 16];
+        self.newest_voice = 0;
     }
 
     fn process(&mut self, buffer: &mut Buffer, _: &mut AuxiliaryBuffers, ctx: &mut impl ProcessContext<Self>) -> ProcessStatus {
@@ -866,22 +2750,67 @@ impl Plugin for ArtcodeBass {
         let flt_type = self.params.filter_type.value();
         let flt_slope = self.params.filter_slope.value();
         let flt_env = self.params.filter_env_amount.value();
+        let flt_key = self.params.filter_keytrack.value();
+        let flt_vel = self.params.filter_velocity.value();
+        // Key tracking leaves the cutoff untouched at this played note; voices sit an octave below it
+        let flt_key_center = self.params.filter_key_center.value() as f32 - 12.0;
+        let flt2_cut = self.params.filter2_cutoff.value();
+        let flt_routing = self.params.filter_routing.value();
+        let vowel = self.params.vowel.value();
+        let vowel_env = self.params.vowel_env.value();
 
         let drive = self.params.drive.value();
         let drive_type = self.params.drive_type.value();
         let low_boost = self.params.low_boost.value();
+        let flt_model = self.params.filter_model.value();
+        let accent_amt = self.params.accent.value();
+        let tone = ToneSettings {
+            drive, drive_type, flt_model, flt_type, flt_slope, flt_res,
+            flt_routing, flt2_type: self.params.filter2_type.value(), flt2_res: self.params.filter2_resonance.value(),
+            vowel, comb_freq: 0.0, comb_damp: 0.0,
+        };
+
+        // Oversampling changes the reported latency, so flush the per-voice
+        // resampler state and let the host know
+        let os_mode = self.params.oversampling.value();
+        if os_mode != self.os_mode {
+            self.os_mode = os_mode;
+            for v in &mut self.voices { v.os_a = [Halfband::new(&HB_TAPS_A); 2]; v.os_b = [Halfband::new(&HB_TAPS_B); 2]; v.os_comp = [0.0; 2]; }
+            ctx.set_latency_samples(oversampling_latency(os_mode));
+        }
 
         let atk = self.params.amp_attack.value();
         let dec = self.params.amp_decay.value();
         let sus = self.params.amp_sustain.value();
         let rel = self.params.amp_release.value();
-
-        let lfo_w = self.params.lfo_waveform.value();
-        let lfo_r = self.params.lfo_rate.value() as f64;
-        let lfo_d = self.params.lfo_depth.value();
-        let lfo_t = self.params.lfo_target.value();
+        let dly = self.params.amp_delay.value();
+        let hold = self.params.amp_hold.value();
+        let atk_curve = self.params.amp_attack_curve.value();
+        let dec_curve = self.params.amp_decay_curve.value();
+        let rel_curve = self.params.amp_release_curve.value();
+        let env_rc = self.params.env_mode.value() == 1;
+        let atk_coef = rc_attack_coef(dt, atk);
+        let dec_coef = rc_stage_coef(dt, dec);
+        let rel_coef = rc_stage_coef(dt, rel);
+        let pitch_env_amt = self.params.pitch_env_amount.value();
+        let vel_amp = self.params.velocity_amp.value();
+        let vel_curve = self.params.velocity_curve.value();
+        let pitch_decay = self.params.pitch_env_decay.value();
+        let pitch_env_mul = 1.0 - rc_stage_coef(dt, pitch_decay);
 
         let porta_time = self.params.portamento.value();
+        let glide_type = self.params.glide_type.value();
+        let glide_coef = rc_stage_coef(dt, porta_time.max(0.001));
+        let bend_coef = 1.0 - (-dt / BEND_SMOOTH_TIME).exp();
+        let mpe_on = self.params.mpe_mode.value() == 1;
+        let a4 = self.params.master_tune.value();
+        if self.tuning_dirty.load(Ordering::Acquire) {
+            if let Ok(t) = self.params.tuning.try_lock() { self.tuning = t.table(); self.tuning_dirty.store(false, Ordering::Release); }
+        }
+        let mpe_range = self.params.mpe_bend_range.value() as f32;
+        let mpe_press_amp = self.params.mpe_pressure_amp.value();
+        let mpe_press_cut = self.params.mpe_pressure_cutoff.value();
+        let mpe_timbre_cut = self.params.mpe_timbre_cutoff.value();
         let gain = self.params.master_gain.value();
 
         let arp_on = self.params.arp_on.value() == 1;
@@ -889,34 +2818,98 @@ impl Plugin for ArtcodeBass {
         let arp_rate_idx = self.params.arp_rate.value();
         let arp_octaves = self.params.arp_octaves.value() + 1;
 
-        // Get tempo from DAW (default 120 BPM if not available)
-        let tempo = ctx.transport().tempo.unwrap_or(120.0) as f32;
-        let arp_interval = arp_rate_to_seconds(arp_rate_idx, tempo);
-
-        let delay_mix = self.params.delay_mix.value();
-        let delay_time = self.params.delay_time.value();
-        let delay_fb = self.params.delay_feedback.value();
-        let reverb_mix = self.params.reverb_mix.value();
-        let reverb_size = self.params.reverb_size.value();
-
         while let Some(ev) = ctx.next_event() {
             match ev {
-                NoteEvent::NoteOn { note, velocity, .. } => {
-                    if arp_on { self.arp_add_note(note, velocity); }
-                    else { self.note_on_voice(note, velocity); }
+                NoteEvent::NoteOn { timing, note, velocity, voice_id, channel, .. } => {
+                    let ended = if arp_on { self.arp_add_note(note, velocity) } else { self.note_on_voice(note, velocity, voice_id, channel) };
+                    Self::send_terminated(ctx, timing, ended);
+                }
+                NoteEvent::PolyModulation { voice_id, poly_modulation_id, normalized_offset, .. } => {
+                    if let Some(v) = self.voices.iter_mut().find(|v| v.active && v.voice_id == voice_id) {
+                        if let Some(o) = v.poly_mod.get_mut(poly_modulation_id as usize) { *o = normalized_offset; }
+                    }
                 }
-                NoteEvent::NoteOff { note, .. } => {
+                NoteEvent::PolyTuning { voice_id, channel, note, tuning, .. } => { for v in self.expression_voices(voice_id, channel, note) { v.tuning = tuning; } }
+                NoteEvent::PolyVolume { voice_id, channel, note, gain, .. } => { for v in self.expression_voices(voice_id, channel, note) { v.expr_gain = gain; } }
+                NoteEvent::PolyPan { voice_id, channel, note, pan, .. } => { for v in self.expression_voices(voice_id, channel, note) { v.pan = pan; } }
+                NoteEvent::PolyBrightness { voice_id, channel, note, brightness, .. } => { for v in self.expression_voices(voice_id, channel, note) { v.brightness = brightness; } }
+                NoteEvent::NoteOff { note, channel, .. } => {
                     if arp_on {
                         self.arp_remove_note(note);
                         if self.arp_note_count == 0 {
                             if let Some(p) = self.arp_playing_note { self.note_off_voice(p); self.arp_playing_note = None; }
                         }
-                    } else { self.note_off_voice(note); }
+                    } else {
+                        // MPE member channels only release their own voices, with or without the pedal
+                        let mpe = self.mpe_member(channel);
+                        let pick = |v: &Voice| v.note == note && (!mpe || v.channel == channel);
+                        if self.sustain { self.sustain_voices(pick); } else { self.release_voices(pick); }
+                    }
+                }
+                // In MPE mode member channels only move their own voices
+                NoteEvent::MidiPitchBend { channel, value, .. } => {
+                    if self.mpe_member(channel) { self.mpe[channel as usize & 15].bend = value * 2.0 - 1.0; } else { self.pitch_bend = value * 2.0 - 1.0; }
+                }
+                NoteEvent::MidiChannelPressure { channel, pressure, .. } => {
+                    // Member channel pressure drives only the MPE amounts, not the Poly AT source
+                    if self.mpe_member(channel) { self.mpe[channel as usize & 15].pressure = pressure; } else { self.aftertouch = pressure; }
+                }
+                NoteEvent::MidiCC { channel, cc: CC_MPE_TIMBRE, value, .. } if self.mpe_member(channel) => self.mpe[channel as usize & 15].timbre = value,
+                NoteEvent::PolyPressure { note, pressure, .. } => {
+                    for v in &mut self.voices { if v.active && v.note == note { v.pressure = pressure; } }
+                }
+                NoteEvent::MidiCC { cc, value, .. } => {
+                    self.midi_cc.store(cc, value);
+                    if self.midi_targets.mark(cc) { ctx.execute_gui(BassTask::ApplyMidiCcs); }
+                    match cc {
+                        CC_MOD_WHEEL => self.mod_wheel = value,
+                        CC_SUSTAIN => {
+                            let down = value >= 0.5;
+                            if self.sustain && !down { self.release_sustained(); }
+                            self.sustain = down;
+                        }
+                        _ => {}
+                    }
                 }
                 _ => {}
             }
         }
 
+        // Get tempo from DAW (default 120 BPM if not available)
+        let tempo = ctx.transport().tempo.unwrap_or(120.0) as f32;
+
+        let mod_slots: [ModSlot; MOD_SLOTS] = std::array::from_fn(|i| self.params.mod_slots[i].to_slot());
+        // Macros act as fixed global offsets on top of the matrix
+        let macro_mod = macro_offsets(&self.params.macros);
+
+        // Synced LFOs: rate from the note division, free ones locked to the host position
+        // unless their rate is modulated
+        let host_beats = if ctx.transport().playing { ctx.transport().pos_beats() } else { None };
+        if let Ok(shapes) = self.params.lfo_shapes.try_lock() { self.lfo_shapes = *shapes; }
+        let mut lfo_cfg = [LfoSettings::default(); 3];
+        for (i, ((st, l), c)) in self.lfos.iter_mut().zip(self.params.lfos()).zip(lfo_cfg.iter_mut()).enumerate() {
+            let sync = l.sync.value() == 1;
+            let rate_dest = MOD_DEST_LFO1_RATE + 2 * i;
+            let rate_routed = macro_mod[rate_dest] != 0.0 || mod_slots.iter().any(|m| m.active() && m.dest as usize == rate_dest);
+            let beats = lfo_division_beats(l.division.value());
+            let rate = if sync { tempo as f64 / 60.0 / beats } else { l.rate.value() as f64 };
+            let fade = l.fade.value();
+            *c = LfoSettings {
+                inc: rate / sr as f64, depth: l.depth.value(), wave: l.wave(), target: l.target.value(),
+                mode: l.mode.value(), start: l.start_phase.value() as f64, fade_step: if fade > 0.0 { dt / fade } else { 1.0 },
+            };
+            if sync && c.mode == LFO_FREE && !rate_routed {
+                if let Some(pos) = host_beats { st.phase = (pos / beats).rem_euclid(1.0); }
+            }
+        }
+        let arp_interval = arp_rate_to_seconds(arp_rate_idx, tempo);
+
+        let delay_mix = self.params.delay_mix.value();
+        let delay_time = self.params.delay_time.value();
+        let delay_fb = self.params.delay_feedback.value();
+        let reverb_mix = self.params.reverb_mix.value();
+        let reverb_size = self.params.reverb_size.value();
+
         if low_boost > 0.0 {
             let boost_db = low_boost * 12.0;
             self.low_shelf_l.set_params(100.0, boost_db, sr);
@@ -924,8 +2917,9 @@ impl Plugin for ArtcodeBass {
         }
 
         let delay_samples = ((delay_time * sr) as usize).min(MAX_DELAY_SAMPLES - 1);
+        let bend_target = bend_semitones(self.pitch_bend, self.params.bend_up.value(), self.params.bend_down.value());
 
-        for mut frame in buffer.iter_samples() {
+        for (sample_idx, mut frame) in buffer.iter_samples().enumerate() {
             // Arpeggiator (synced to DAW tempo) - artcode_synth style
             if arp_on && self.arp_note_count > 0 {
                 self.arp_timer += dt;
@@ -934,53 +2928,136 @@ impl Plugin for ArtcodeBass {
                     // Release previous note, then play next
                     if let Some(p) = self.arp_playing_note { self.note_off_voice(p); }
                     if let Some((n, v)) = self.arp_next_note(arp_mode, arp_octaves) {
-                        self.note_on_voice(n, v);
+                        let ended = self.note_on_voice(n, v, None, 0);
+                        Self::send_terminated(ctx, sample_idx as u32, ended);
                         self.arp_playing_note = Some(n);
                     }
                 }
             }
 
-            // LFO
-            let lfo = gen_wave(self.lfo_phase, lfo_w);
-            self.lfo_phase += lfo_r / sr as f64;
-            if self.lfo_phase >= 1.0 { self.lfo_phase -= 1.0; }
+            // Global mod sources and the matrix slots that only use them
+            let mut msrc = ModSources { mod_wheel: self.mod_wheel, aftertouch: self.aftertouch, ..ModSources::default() };
+            let mut lfo_raw = [0.0f32; 3];
+            for (i, ((st, c), shape)) in self.lfos.iter().zip(&lfo_cfg).zip(&self.lfo_shapes).enumerate() {
+                lfo_raw[i] = st.value(c, shape);
+                msrc.lfo[i] = lfo_raw[i] * st.fade;
+            }
+            let mut gmod = macro_mod;
+            for m in mod_slots.iter().filter(|m| m.active() && !m.is_voice()) { gmod[m.dest as usize] += m.value(&msrc); }
+            // Voice sources reach the global destinations through the newest voice
+            if let Some(v) = self.voices.get(self.newest_voice).filter(|v| v.active) {
+                let vsrc = v.mod_sources(&msrc);
+                for m in mod_slots.iter().filter(|m| m.active() && m.is_voice() && is_global_dest(m.dest as usize)) { gmod[m.dest as usize] += m.value(&vsrc); }
+            }
+
+            // LFOs, summed per target (0 = pitch, 1 = cutoff, 2 = amp, 3 = vowel)
+            let (mut lfo_pitch, mut lfo_cut, mut lfo_amp, mut lfo_vowel) = (0.0f32, 0.0f32, 1.0f32, 0.0f32);
+            for (i, ((st, c), l)) in self.lfos.iter_mut().zip(&lfo_cfg).zip(self.params.lfos()).enumerate() {
+                let lfo = lfo_raw[i];
+                let depth = mod_param(&l.depth, c.depth, gmod[MOD_DEST_LFO1_RATE + 2 * i + 1]) * st.fade;
+                let rate_mod = gmod[MOD_DEST_LFO1_RATE + 2 * i];
+                let (phase_mod, fade_mod) = (gmod[MOD_DEST_LFO1_PHASE + 2 * i], gmod[MOD_DEST_LFO1_PHASE + 2 * i + 1]);
+                st.start_mod = mod_param(&l.start_phase, c.start as f32, phase_mod) as f64 - c.start;
+                let c = &if fade_mod != 0.0 {
+                    let fade = mod_param(&l.fade, l.fade.value(), fade_mod);
+                    LfoSettings { fade_step: if fade > 0.0 { dt / fade } else { 1.0 }, ..*c }
+                } else { *c };
+                if rate_mod == 0.0 { st.advance(c); }
+                else if l.sync.value() == 1 {
+                    let octaves = (rate_mod * LFO_SYNC_MOD_OCTAVES).round();
+                    st.advance(&LfoSettings { inc: c.inc * 2.0_f64.powf(octaves as f64), ..*c });
+                } else {
+                    st.advance(&LfoSettings { inc: mod_param(&l.rate, l.rate.value(), rate_mod) as f64 / sr as f64, ..*c });
+                }
+                match c.target {
+                    0 => lfo_pitch += lfo * depth * 0.5,
+                    1 => lfo_cut += lfo * depth * 2000.0,
+                    2 => lfo_amp *= 1.0 - depth * 0.5 * (1.0 - lfo),
+                    _ => lfo_vowel += lfo * depth * 0.5,
+                }
+            }
+            let pm = 2.0_f32.powf(lfo_pitch);
+
+            // Pitch bend, smoothed so wheel steps don't click
+            self.bend_semis += (bend_target - self.bend_semis) * bend_coef;
+            let bend = self.bend_semis;
 
-            let mut out = 0.0f32;
+            let (mut out_l, mut out_r) = (0.0f32, 0.0f32);
 
-            for v in &mut self.voices {
+            for (vi, v) in self.voices.iter_mut().enumerate() {
                 if !v.active { continue; }
 
-                // Portamento
-                if porta_time > 0.001 {
-                    let rate = 1.0 / (porta_time * sr);
-                    let diff = v.target_note - v.current_note;
-                    if diff.abs() > 0.01 { v.current_note += diff.signum() * rate.min(diff.abs()); }
-                    else { v.current_note = v.target_note; }
-                } else { v.current_note = v.target_note; }
+                // Mod matrix: global offsets plus the slots driven by this voice. It runs ahead of
+                // the envelope so the envelope times can be modulated; the amp env source lags one sample
+                let mut vmod = gmod;
+                let vsrc = v.mod_sources(&msrc);
+                for m in mod_slots.iter().filter(|m| m.active() && m.is_voice() && !is_global_dest(m.dest as usize)) { vmod[m.dest as usize] += m.value(&vsrc); }
+                let p = &self.params;
+                // MPE: the voice follows its own channel's bend, pressure and timbre
+                let mut mpe_amp = 1.0;
+                if mpe_on && v.channel != MPE_MASTER_CHANNEL {
+                    let ch = self.mpe[v.channel as usize & 15];
+                    v.mpe_bend += (ch.bend * mpe_range - v.mpe_bend) * bend_coef;
+                    let press_amp = mod_param(&p.mpe_pressure_amp, mpe_press_amp, vmod[MOD_DEST_MPE_PRESS_AMP]);
+                    let press_cut = mod_param(&p.mpe_pressure_cutoff, mpe_press_cut, vmod[MOD_DEST_MPE_PRESS_CUT]);
+                    let timbre_cut = mod_param(&p.mpe_timbre_cutoff, mpe_timbre_cut, vmod[MOD_DEST_MPE_TIMBRE_CUT]);
+                    mpe_amp = 1.0 - press_amp * (1.0 - ch.pressure);
+                    vmod[MOD_DEST_CUTOFF] += ch.pressure * press_cut + (ch.timbre - 0.5) * timbre_cut;
+                }
+                // CLAP poly modulation and brightness expression, in the same normalized units
+                vmod[MOD_DEST_CUTOFF] += v.poly_mod[POLY_MOD_CUTOFF as usize] + (v.brightness - 0.5);
+                vmod[MOD_DEST_RESO] += v.poly_mod[POLY_MOD_RESO as usize];
+                vmod[MOD_DEST_DRIVE] += v.poly_mod[POLY_MOD_DRIVE as usize];
+                let porta_time = mod_param(&p.portamento, porta_time, vmod[MOD_DEST_PORTA]);
+                let glide_coef = if vmod[MOD_DEST_PORTA] != 0.0 { rc_stage_coef(dt, porta_time.max(0.001)) } else { glide_coef };
+                let (dly, hold) = (mod_param(&p.amp_delay, dly, vmod[MOD_DEST_ENV_DELAY]), mod_param(&p.amp_hold, hold, vmod[MOD_DEST_HOLD]));
+                let (atk, dec) = (mod_param(&p.amp_attack, atk, vmod[MOD_DEST_ATTACK]), mod_param(&p.amp_decay, dec, vmod[MOD_DEST_DECAY]));
+                let (sus, rel) = (mod_param(&p.amp_sustain, sus, vmod[MOD_DEST_SUSTAIN]), mod_param(&p.amp_release, rel, vmod[MOD_DEST_RELEASE]));
+                let atk_curve = mod_param(&p.amp_attack_curve, atk_curve, vmod[MOD_DEST_ATK_CURVE]);
+                let dec_curve = mod_param(&p.amp_decay_curve, dec_curve, vmod[MOD_DEST_DEC_CURVE]);
+                let rel_curve = mod_param(&p.amp_release_curve, rel_curve, vmod[MOD_DEST_REL_CURVE]);
+                let atk_coef = if vmod[MOD_DEST_ATTACK] != 0.0 { rc_attack_coef(dt, atk) } else { atk_coef };
+                let dec_coef = if vmod[MOD_DEST_DECAY] != 0.0 { rc_stage_coef(dt, dec) } else { dec_coef };
+                let rel_coef = if vmod[MOD_DEST_RELEASE] != 0.0 { rc_stage_coef(dt, rel) } else { rel_coef };
+
+                v.glide(porta_time, glide_type, glide_coef, sr);
 
                 // Envelope with smooth transitions
                 let env = match v.env_stage {
                     1 => { 
                         v.env_time += dt;
-                        if v.env_time >= atk { 
-                            v.env_stage = 2; v.env_time = 0.0; v.env_value = 1.0; 
+                        if env_rc {
+                            v.env_value += (RC_ATTACK_TARGET - v.env_value) * atk_coef;
+                            if v.env_value >= 1.0 { v.env_stage = if hold > 0.0 { ENV_HOLD } else { 2 }; v.env_time = 0.0; v.env_value = 1.0; }
+                        } else if v.env_time >= atk { 
+                            v.env_stage = if hold > 0.0 { ENV_HOLD } else { 2 }; v.env_time = 0.0; v.env_value = 1.0; 
                         } else { 
-                            // Linear attack from atk_start to 1.0
-                            let progress = v.env_time / atk;
+                            // Curved attack from atk_start to 1.0
+                            let progress = env_curve(v.env_time / atk, atk_curve);
                             v.env_value = v.atk_start + (1.0 - v.atk_start) * progress;
                         }
                         v.env_value
                     }
-                    2 => { v.env_time += dt; if v.env_time >= dec { v.env_stage = 3; v.env_value = sus; } else { v.env_value = 1.0 - (1.0 - sus) * v.env_time / dec; } v.env_value }
+                    2 => {
+                        v.env_time += dt;
+                        if v.env_time >= dec { v.env_stage = 3; v.env_value = sus; }
+                        else if env_rc { v.env_value += (sus - v.env_value) * dec_coef; }
+                        else { v.env_value = 1.0 - (1.0 - sus) * env_curve(v.env_time / dec, dec_curve); }
+                        v.env_value
+                    }
                     3 => sus,
+                    ENV_DELAY => { v.env_time += dt; if v.env_time >= dly { v.env_stage = 1; v.env_time = 0.0; } v.env_value }
+                    ENV_HOLD => { v.env_time += dt; if v.env_time >= hold { v.env_stage = 2; v.env_time = 0.0; } v.env_value }
                     4 => { 
                         v.env_time += dt; 
                         if v.env_time >= rel { 
                             v.env_value = 0.0;
-                            v.active = false; 
+                            v.active = false;
+                            ctx.send_event(NoteEvent::VoiceTerminated { timing: sample_idx as u32, voice_id: Some(v.voice_id), channel: v.channel, note: v.note });
                             0.0 
                         } else { 
-                            v.env_value = v.rel_start * (1.0 - v.env_time / rel);
+                            if env_rc { v.env_value -= v.env_value * rel_coef; }
+                            else { v.env_value = v.rel_start * (1.0 - env_curve(v.env_time / rel, rel_curve)); }
                             v.env_value
                         } 
                     }
@@ -989,8 +3066,27 @@ impl Plugin for ArtcodeBass {
 
                 if !v.active { continue; }
 
-                let base_freq = 440.0 * 2.0_f32.powf((v.current_note - 69.0) / 12.0);
-                let pm = if lfo_t == 0 { 2.0_f32.powf(lfo * lfo_d * 0.5) } else { 1.0 };
+                let osc1_det = mod_param(&p.osc1_detune, osc1_det, vmod[MOD_DEST_OSC1_DET]);
+                let osc2_det = mod_param(&p.osc2_detune, osc2_det, vmod[MOD_DEST_OSC2_DET]);
+                let osc2_mix = mod_param(&p.osc2_mix, osc2_mix, vmod[MOD_DEST_OSC2_MIX]);
+                let sub_vol = mod_param(&p.sub_volume, sub_vol, vmod[MOD_DEST_SUB_VOL]);
+                let spread = mod_param(&p.unison_spread, spread / 50.0, vmod[MOD_DEST_SPREAD]) * 50.0;
+                let flt_cut = mod_param(&p.filter_cutoff, flt_cut, vmod[MOD_DEST_CUTOFF]);
+                let flt_res = mod_param(&p.filter_resonance, flt_res, vmod[MOD_DEST_RESO]);
+                let flt_env = mod_param(&p.filter_env_amount, flt_env, vmod[MOD_DEST_FLT_ENV]);
+                let flt2_cut = mod_param(&p.filter2_cutoff, flt2_cut, vmod[MOD_DEST_CUTOFF2]);
+                let accent_amt = mod_param(&p.accent, accent_amt, vmod[MOD_DEST_ACCENT]);
+                let vowel_env = mod_param(&p.vowel_env, vowel_env, vmod[MOD_DEST_VOWEL_ENV]);
+                let flt_key = mod_param(&p.filter_keytrack, flt_key, vmod[MOD_DEST_KEYTRACK]);
+                let flt_vel = mod_param(&p.filter_velocity, flt_vel, vmod[MOD_DEST_FLT_VEL]);
+                let vel_amp = mod_param(&p.velocity_amp, vel_amp, vmod[MOD_DEST_VEL_AMP]);
+                let pitch_env_amt = mod_param(&p.pitch_env_amount, pitch_env_amt, vmod[MOD_DEST_PITCH_ENV]);
+                let pitch_env_mul = if vmod[MOD_DEST_PITCH_DECAY] != 0.0 { 1.0 - rc_stage_coef(dt, mod_param(&p.pitch_env_decay, pitch_decay, vmod[MOD_DEST_PITCH_DECAY])) } else { pitch_env_mul };
+
+                // Pitch envelope: starts at the full amount and decays to the played note
+                let a4 = mod_param(&p.master_tune, a4, vmod[MOD_DEST_TUNE]);
+                let base_freq = a4 * 2.0_f32.powf((v.current_note + bend + v.mpe_bend + v.tuning + pitch_env_amt * v.pitch_env - 69.0) / 12.0);
+                v.pitch_env *= pitch_env_mul;
 
                 // Unison oscillators
                 let mut osc1_out = 0.0f32;
@@ -1019,51 +3115,55 @@ impl Plugin for ArtcodeBass {
                 v.sub_phase = (v.sub_phase + sub_freq / sr) % 1.0;
                 let sub_out = (v.sub_phase * std::f32::consts::TAU).sin();
 
-                let osc_mix = osc1_out * (1.0 - osc2_mix) + osc2_out * osc2_mix + sub_out * sub_vol;
-
-                // Drive
-                let driven = apply_drive(osc_mix, drive, drive_type);
-
-                // Filter
-                let cm = env * flt_env * 5000.0 + if lfo_t == 1 { lfo * lfo_d * 2000.0 } else { 0.0 };
-                let cut = (flt_cut + cm).clamp(20.0, 20000.0);
-                let g = (std::f32::consts::PI * cut / sr).tan().min(1.0);
-                let k = 2.0 - 2.0 * flt_res.min(0.98);
-
-                // Clamp filter state to prevent blowup
-                v.filter_lp = v.filter_lp.clamp(-10.0, 10.0);
-                v.filter_bp = v.filter_bp.clamp(-10.0, 10.0);
-
-                let hp = (driven - v.filter_lp - k * v.filter_bp) / (1.0 + k * g + g * g);
-                let bp = g * hp + v.filter_bp;
-                let lp = g * bp + v.filter_lp;
-                v.filter_bp = (bp + g * hp).clamp(-10.0, 10.0);
-                v.filter_lp = (lp + g * bp).clamp(-10.0, 10.0);
-
-                let mut flt_out = match flt_type { 0 => lp, 1 => hp, _ => bp };
-
-                // 24dB mode
-                if flt_slope == 1 {
-                    v.filter_lp2 = v.filter_lp2.clamp(-10.0, 10.0);
-                    v.filter_bp2 = v.filter_bp2.clamp(-10.0, 10.0);
+                let osc_only = osc1_out * (1.0 - osc2_mix) + osc2_out * osc2_mix;
+                // Split routing sends the sub through filter 1 and the oscillators through filter 2
+                let tone_in = if flt_routing == FILTER_ROUTE_SPLIT { [sub_out * sub_vol, osc_only] } else { [osc_only + sub_out * sub_vol, 0.0] };
 
-                    let hp2 = (flt_out - v.filter_lp2 - k * v.filter_bp2) / (1.0 + k * g + g * g);
-                    let bp2 = g * hp2 + v.filter_bp2;
-                    let lp2 = g * bp2 + v.filter_lp2;
-                    v.filter_bp2 = (bp2 + g * hp2).clamp(-10.0, 10.0);
-                    v.filter_lp2 = (lp2 + g * bp2).clamp(-10.0, 10.0);
-                    flt_out = match flt_type { 0 => lp2, 1 => hp2, _ => bp2 };
+                // Accented notes push the filter envelope and resonance harder
+                let mut vtone = ToneSettings {
+                    flt_res,
+                    flt2_res: mod_param(&p.filter2_resonance, tone.flt2_res, vmod[MOD_DEST_RESO2]),
+                    drive: mod_param(&p.drive, tone.drive, vmod[MOD_DEST_DRIVE]),
+                    ..tone
+                };
+                let mut venv = flt_env;
+                if v.accent && accent_amt > 0.0 {
+                    venv *= 1.0 + accent_amt;
+                    vtone.flt_res = (flt_res + accent_amt * 0.3).min(0.99);
                 }
+                vtone.vowel = mod_param(&p.vowel, vowel, vmod[MOD_DEST_VOWEL]) + env * vowel_env + lfo_vowel;
+                vtone.comb_freq = (base_freq * pm).max(20.0);
+
+                // Drive + filter (oversampled when enabled)
+                let cm = env * venv * 5000.0 + lfo_cut;
+                let key_oct = (v.current_note - flt_key_center) / 12.0 * flt_key;
+                let vel_oct = v.velocity * flt_vel * FILTER_VEL_OCTAVES;
+                let track = 2.0_f32.powf(key_oct + vel_oct);
+                let cut = (flt_cut * track + cm).clamp(20.0, 20000.0);
+                let cut2 = (flt2_cut * track + cm).clamp(20.0, 20000.0);
+                let flt_out = v.tone_stage(tone_in, [cut, cut2], sr, os_mode, &vtone, &mut self.combs[vi]);
+
+                // Master gain goes on per voice so CLAP poly gain modulation lands on the same parameter
+                let vgain = mod_param(&p.master_gain, gain, vmod[MOD_DEST_VOLUME] + v.poly_mod[POLY_MOD_GAIN as usize]);
+                let vout = flt_out * env * velocity_gain(v.velocity, vel_curve, vel_amp) * lfo_amp * vgain * v.expr_gain * mpe_amp;
+                out_l += vout * (1.0 - v.pan).min(1.0);
+                out_r += vout * (1.0 + v.pan).min(1.0);
+            }
 
-                // Safety clamp output
-                flt_out = flt_out.clamp(-10.0, 10.0);
-
-                let am = if lfo_t == 2 { 1.0 - lfo_d * 0.5 * (1.0 - lfo) } else { 1.0 };
-                out += flt_out * env * v.velocity * am;
+            // Global mod matrix destinations
+            let p = &self.params;
+            let low_boost = mod_param(&p.low_boost, low_boost, gmod[MOD_DEST_LOW_BOOST]);
+            if gmod[MOD_DEST_LOW_BOOST] != 0.0 {
+                self.low_shelf_l.set_params(100.0, low_boost * 12.0, sr);
+                self.low_shelf_r.set_params(100.0, low_boost * 12.0, sr);
             }
+            let delay_mix = mod_param(&p.delay_mix, delay_mix, gmod[MOD_DEST_DELAY_MIX]);
+            let delay_fb = mod_param(&p.delay_feedback, delay_fb, gmod[MOD_DEST_DELAY_FB]);
+            let reverb_mix = mod_param(&p.reverb_mix, reverb_mix, gmod[MOD_DEST_REVERB_MIX]);
+            let reverb_size = mod_param(&p.reverb_size, reverb_size, gmod[MOD_DEST_REVERB_SIZE]);
+            let delay_samples = if gmod[MOD_DEST_DELAY_TIME] != 0.0 { ((mod_param(&p.delay_time, delay_time, gmod[MOD_DEST_DELAY_TIME]) * sr) as usize).min(MAX_DELAY_SAMPLES - 1) } else { delay_samples };
 
             // Low boost EQ
-            let (mut out_l, mut out_r) = (out, out);
             if low_boost > 0.0 {
                 out_l = self.low_shelf_l.process(out_l);
                 out_r = self.low_shelf_r.process(out_r);
@@ -1097,8 +3197,8 @@ impl Plugin for ArtcodeBass {
             self.dc_filter_r = new_dc_r;
 
             let mut samples = frame.iter_mut().collect::<Vec<_>>();
-            *samples[0] = (out_l * gain).clamp(-1.0, 1.0);
-            *samples[1] = (out_r * gain).clamp(-1.0, 1.0);
+            *samples[0] = out_l.clamp(-1.0, 1.0);
+            *samples[1] = out_r.clamp(-1.0, 1.0);
         }
 
         ProcessStatus::Normal
@@ -1110,7 +3210,8 @@ impl ClapPlugin for ArtcodeBass {
     const CLAP_DESCRIPTION: Option<&'static str> = Some("Fat bass synthesizer");
     const CLAP_MANUAL_URL: Option<&'static str> = None;
     const CLAP_SUPPORT_URL: Option<&'static str> = None;
-    const CLAP_FEATURES: &'static [ClapFeature] = &[ClapFeature::Instrument, ClapFeature::Synthesizer, ClapFeature::Mono];
+    const CLAP_FEATURES: &'static [ClapFeature] = &[ClapFeature::Instrument, ClapFeature::Synthesizer, ClapFeature::Stereo];
+    const CLAP_POLY_MODULATION_CONFIG: Option<PolyModulationConfig> = Some(PolyModulationConfig { max_voice_capacity: MAX_VOICES as u32, supports_overlapping_voices: true });
 }
 
 impl Vst3Plugin for ArtcodeBass {
@@ -1120,3 +3221,529 @@ impl Vst3Plugin for ArtcodeBass {
 
 nih_export_clap!(ArtcodeBass);
 nih_export_vst3!(ArtcodeBass);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TET12: &str = "! 12-TET\n12 tone equal temperament\n12\n100.0\n200.0\n300.0\n400.0\n500.0\n600.0\n700.0\n800.0\n900.0\n1000.0\n1100.0\n2/1\n";
+
+    #[test]
+    fn equal_temperament_is_identity() {
+        for tuning in [Tuning::default(), Tuning::from_scala(TET12, None).unwrap()] {
+            let t = tuning.table();
+            assert_eq!(t.span, 12);
+            for key in 0..128 {
+                assert!(t.mapped[key]);
+                assert!((t.pitch[key] - key as f32).abs() < 1e-3, "key {} -> {}", key, t.pitch[key]);
+            }
+        }
+    }
+
+    #[test]
+    fn scl_ratios_and_cents() {
+        let (name, cents) = Tuning::parse_scl("! just.scl\n!\nJust third\n 3\n 9/8\n 386.3137 ! major third\n 2/1\n").unwrap();
+        assert_eq!(name, "Just third");
+        assert!((cents[0] - 203.91).abs() < 0.01);
+        assert!((cents[1] - 386.3137).abs() < 1e-9);
+        assert!((cents[2] - 1200.0).abs() < 1e-9);
+        // Key 69 is three periods above the middle key and stays at 440 Hz
+        let t = Tuning::from_scala("Just third\n3\n9/8\n386.3137\n2/1\n", None).unwrap().table();
+        assert_eq!(t.span, 3);
+        assert!((t.pitch[69] - 69.0).abs() < 1e-3);
+        assert!((t.pitch[60] - 33.0).abs() < 1e-3);
+        assert!((t.pitch[61] - 35.0391).abs() < 1e-3);
+        assert!((t.pitch[63] - 45.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn kbm_x_leaves_key_unmapped() {
+        let kbm = "! skip C#\n12\n0\n127\n60\n69\n440.0\n12\n0\nx\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n";
+        let t = Tuning::from_scala(TET12, Some(kbm)).unwrap().table();
+        assert!(!t.mapped[61] && !t.mapped[73] && !t.mapped[49]);
+        assert!(t.mapped[60] && t.mapped[62]);
+        assert!((t.pitch[62] - 62.0).abs() < 1e-3);
+        assert!((t.pitch[69] - 69.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bad_input_is_an_error() {
+        assert!(Tuning::parse_scl("").is_err());
+        assert!(Tuning::parse_scl("short\n3\n100.0\n2/1\n").is_err());
+        assert!(Tuning::parse_scl("zero\n1\n3/0\n").is_err());
+        assert!(Tuning::parse_scl("none\n0\n").is_err());
+        let kbm = |size: &str, middle: &str, period: &str, entry: &str| format!("{}\n0\n127\n{}\n69\n440.0\n{}\n{}\n", size, middle, period, entry);
+        assert!(Tuning::parse_kbm(&kbm("1", "60", "1", "0")).is_ok());
+        assert!(Tuning::parse_kbm(&kbm("-1", "60", "1", "0")).is_err());
+        assert!(Tuning::parse_kbm(&kbm("99999999999999", "60", "1", "0")).is_err());
+        assert!(Tuning::parse_kbm(&kbm("1", "200", "1", "0")).is_err());
+        assert!(Tuning::parse_kbm(&kbm("1", "60", "-5", "0")).is_err());
+        assert!(Tuning::parse_kbm(&kbm("1", "60", "1", "abc")).is_err());
+        assert!(Tuning::parse_kbm("12\n0\n").is_err());
+    }
+
+    // Impulse response of the resampler chain of an oversampling mode, with the filter stage left out
+    fn resampler_impulse(os_mode: i32, len: usize) -> Vec<f32> {
+        let (mut a, mut b, mut comp) = (Halfband::new(&HB_TAPS_A), Halfband::new(&HB_TAPS_B), 0.0f32);
+        (0..len).map(|n| {
+            let up = a.upsample(if n == 0 { 1.0 } else { 0.0 });
+            if os_mode == 1 { return a.downsample(up); }
+            let mid = up.map(|x| { let d = comp; comp = x; let up2 = b.upsample(d); b.downsample(up2) });
+            a.downsample(mid)
+        }).collect()
+    }
+
+    #[test]
+    fn halfband_dc_gain_and_latency() {
+        for os_mode in [1, 2] {
+            let h = resampler_impulse(os_mode, 64);
+            let dc: f32 = h.iter().sum();
+            assert!((dc - 1.0).abs() < 1e-3, "mode {} dc gain {}", os_mode, dc);
+            let peak = (0..h.len()).max_by(|&a, &b| h[a].abs().total_cmp(&h[b].abs())).unwrap();
+            assert_eq!(peak as u32, oversampling_latency(os_mode));
+        }
+        assert_eq!(oversampling_latency(0), 0);
+    }
+
+    fn tone(flt_model: i32, flt_type: i32, flt_res: f32) -> ToneSettings {
+        ToneSettings { drive: 0.0, drive_type: 0, flt_model, flt_type, flt_slope: 1, flt_res, flt_routing: 0, flt2_type: 0, flt2_res: 0.0, vowel: 0.0, comb_freq: 100.0, comb_damp: 0.0 }
+    }
+
+    // Peak output over the second half of n samples fed through a fresh voice's tone stage at 48 kHz
+    fn tone_peak(s: &ToneSettings, cut: f32, n: usize, input: impl Fn(usize) -> f32) -> f32 {
+        let (mut v, mut comb) = (Voice::default(), CombFilter::new());
+        let mut peak = 0.0f32;
+        for i in 0..n {
+            let y = v.tone_stage([input(i), 0.0], [cut, cut], 48000.0, 0, s, &mut comb);
+            if i >= n / 2 { peak = peak.max(y.abs()); }
+        }
+        peak
+    }
+
+    fn sine(freq: f32) -> impl Fn(usize) -> f32 { move |i| (std::f32::consts::TAU * freq * i as f32 / 48000.0).sin() }
+
+    fn impulse(i: usize) -> f32 { if i == 0 { 1.0 } else { 0.0 } }
+
+    #[test]
+    fn ladder_passes_dc_and_cuts_highs() {
+        let s = tone(1, 0, 0.0);
+        assert!((tone_peak(&s, 500.0, 4800, |_| 0.1) - 0.1).abs() < 0.01);
+        assert!(tone_peak(&s, 200.0, 4800, sine(5000.0)) < 0.01);
+    }
+
+    #[test]
+    fn ladder_self_oscillates_at_full_resonance() {
+        assert!(tone_peak(&tone(1, 0, 1.0), 1000.0, 48000, impulse) > 0.1);
+        assert!(tone_peak(&tone(1, 0, 0.5), 1000.0, 48000, impulse) < 1e-3);
+    }
+
+    #[test]
+    fn diode_passes_dc_and_cuts_highs() {
+        // Kept quiet, the asymmetric saturation would otherwise rectify the sine into DC
+        let (s, hi) = (tone(2, 0, 0.0), sine(5000.0));
+        assert!((tone_peak(&s, 500.0, 4800, |_| 0.1) - 0.1).abs() < 0.01);
+        assert!(tone_peak(&s, 200.0, 4800, |i| 0.1 * hi(i)) < 0.005);
+    }
+
+    #[test]
+    fn diode_resonance_keeps_the_bass() {
+        // The resonance feedback is highpassed, so DC isn't thinned out as resonance rises
+        let dc = |res| tone_peak(&tone(2, 0, res), 500.0, 9600, |_| 0.1);
+        assert!(dc(0.9) >= dc(0.0));
+    }
+
+    #[test]
+    fn svf_notch_cuts_and_peak_boosts_at_cutoff() {
+        let (notch, peak) = (tone(0, 3, 0.5), tone(0, 4, 0.5));
+        assert!(tone_peak(&notch, 1000.0, 48000, sine(1000.0)) < 0.01);
+        assert!(tone_peak(&notch, 1000.0, 48000, sine(100.0)) > 0.9);
+        assert!(tone_peak(&peak, 1000.0, 48000, sine(1000.0)) > 2.0 * tone_peak(&peak, 1000.0, 48000, sine(100.0)));
+    }
+
+    #[test]
+    fn comb_echo_sign_follows_mode() {
+        // 480 samples of delay, undamped loop
+        let echo = |fb: f32| { let mut c = CombFilter::new(); (0..=480).map(|i| c.process(impulse(i), 480.0, fb, 1.0)).last().unwrap() };
+        assert!(echo(0.5) > 0.3 && echo(-0.5) < -0.3);
+        assert_eq!(echo(0.0), 0.0);
+    }
+
+    #[test]
+    fn filter_mode_migrates_to_filter_type() {
+        let mut state = PluginState { version: String::new(), params: [("filter_mode".to_string(), ParamValue::I32(3))].into(), fields: Default::default() };
+        ArtcodeBass::filter_state(&mut state);
+        assert!(matches!(state.params.get("filter_type"), Some(ParamValue::I32(5))));
+        assert!(!state.params.contains_key("filter_mode"));
+    }
+
+    // Settled output of drive_filter for constant sub and osc inputs, both filters at 500 Hz
+    fn routed_dc(routing: i32, flt2_type: i32, input: [f32; 2]) -> f32 {
+        let s = ToneSettings { flt_routing: routing, flt2_type, ..tone(0, 0, 0.0) };
+        let (mut v, mut comb) = (Voice::default(), CombFilter::new());
+        let g = (std::f32::consts::PI * 500.0 / 48000.0).tan();
+        (0..4800).map(|_| v.drive_filter(input, [g, g], 48000.0, &s, &mut comb)).last().unwrap()
+    }
+
+    #[test]
+    fn filter_routings_at_dc() {
+        assert!((routed_dc(0, 0, [0.1, 0.0]) - 0.1).abs() < 1e-3);
+        // Parallel LP + LP stays at unity instead of doubling
+        assert!((routed_dc(FILTER_ROUTE_PARALLEL, 0, [0.1, 0.0]) - 0.1).abs() < 1e-3);
+        assert!(routed_dc(FILTER_ROUTE_SERIAL, 1, [0.1, 0.0]).abs() < 1e-3);
+        // Split: the sub only reaches filter 1 and the oscillators only filter 2
+        assert!((routed_dc(FILTER_ROUTE_SPLIT, 1, [0.1, 0.0]) - 0.05).abs() < 1e-3);
+        assert!(routed_dc(FILTER_ROUTE_SPLIT, 1, [0.0, 0.1]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn vowel_moves_the_formants() {
+        let vowel = |v: f32, f: f32| tone_peak(&ToneSettings { vowel: v, ..tone(0, FILTER_VOWEL, 0.5) }, 1000.0, 9600, sine(f));
+        // A has F1 at 800 Hz, I at 300 Hz
+        assert!(vowel(0.0, 800.0) > 2.0 * vowel(0.0, 300.0));
+        assert!(vowel(0.5, 300.0) > 2.0 * vowel(0.5, 800.0));
+        assert!(vowel(0.0, 800.0) > 1.0);
+        assert!(vowel(1.0, 325.0).is_finite());
+    }
+
+    #[test]
+    fn env_curve_keeps_endpoints_and_order() {
+        for curve in [-1.0, -0.4, 0.0, 0.5, 1.0] {
+            assert!(env_curve(0.0, curve).abs() < 1e-6 && (env_curve(1.0, curve) - 1.0).abs() < 1e-6);
+            assert!((1..=10).all(|i| env_curve(i as f32 / 10.0, curve) > env_curve((i - 1) as f32 / 10.0, curve)));
+        }
+        assert!(env_curve(0.5, 1.0) > 0.5 && env_curve(0.5, -1.0) < 0.5);
+    }
+
+    #[test]
+    fn rc_stages_take_the_set_time() {
+        let (dt, time) = (1.0 / 48000.0, 0.1);
+        let (mut atk, mut dec) = (0.0f32, 1.0f32);
+        for _ in 0..4800 {
+            atk += (RC_ATTACK_TARGET - atk) * rc_attack_coef(dt, time);
+            dec -= dec * rc_stage_coef(dt, time);
+        }
+        assert!((atk - 1.0).abs() < 1e-3);
+        assert!((dec - (-RC_STAGE_TCS).exp()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn pitch_env_starts_full_and_drops_over_its_decay() {
+        let mut bass = ArtcodeBass::default();
+        bass.note_on_voice(36, 1.0, None, 0);
+        assert_eq!(bass.voices[0].pitch_env, 1.0);
+        // 80 ms at 48 kHz, as on the 808 Sub preset
+        let mul = 1.0 - rc_stage_coef(1.0 / 48000.0, 0.08);
+        let left = (0..3840).fold(1.0f32, |env, _| env * mul);
+        assert!((left - 0.01).abs() < 1e-3);
+    }
+
+    #[test]
+    fn velocity_gain_curves_and_amount() {
+        for curve in 0..4 { assert_eq!(velocity_gain(0.3, curve, 0.0), 1.0); }
+        assert_eq!(velocity_gain(0.3, 3, 1.0), 1.0);
+        assert!((velocity_gain(0.5, 0, 1.0) - 0.5).abs() < 1e-6);
+        assert!((velocity_gain(0.25, 1, 1.0) - 0.5).abs() < 1e-6);
+        assert!((velocity_gain(0.5, 2, 1.0) - 0.25).abs() < 1e-6);
+        // Half amount lands halfway between fixed and the curve
+        assert!((velocity_gain(0.5, 0, 0.5) - 0.75).abs() < 1e-6);
+    }
+
+    fn bass_with(set: impl FnOnce(&mut BassParams)) -> ArtcodeBass {
+        let mut params = BassParams::default();
+        set(&mut params);
+        ArtcodeBass { params: Arc::new(params), ..ArtcodeBass::default() }
+    }
+
+    fn retrig(mode: i32) -> ArtcodeBass { bass_with(|p| p.env_retrigger = IntParam::new("Retrig", mode, IntRange::Linear { min: 0, max: 2 })) }
+
+    #[test]
+    fn retrigger_modes() {
+        // Reset: a repeat gets a fresh voice from zero
+        let mut bass = retrig(0);
+        bass.note_on_voice(40, 1.0, None, 0);
+        bass.voices[0].env_value = 0.6;
+        bass.note_on_voice(40, 1.0, None, 0);
+        assert!(bass.voices[1].active && bass.voices[1].env_value == 0.0 && bass.voices[1].atk_start == 0.0);
+
+        // Restart: the sounding voice attacks again from its current level
+        let mut bass = retrig(RETRIG_RESTART);
+        bass.note_on_voice(40, 1.0, None, 0);
+        bass.voices[0].env_stage = 2; bass.voices[0].env_value = 0.6;
+        bass.note_on_voice(40, 1.0, None, 0);
+        assert!(!bass.voices[1].active);
+        assert_eq!((bass.voices[0].env_stage, bass.voices[0].atk_start), (1, 0.6));
+
+        // Legato: a held voice takes the new note without touching its envelope
+        let mut bass = retrig(RETRIG_LEGATO);
+        bass.note_on_voice(40, 1.0, None, 0);
+        bass.voices[0].env_stage = 2; bass.voices[0].env_value = 0.6;
+        bass.note_on_voice(43, 1.0, None, 0);
+        assert!(!bass.voices[1].active);
+        assert_eq!((bass.voices[0].note, bass.voices[0].env_stage, bass.voices[0].env_value), (43, 2, 0.6));
+        // Once released the next note starts its own voice
+        bass.voices[0].env_stage = 4;
+        bass.note_on_voice(45, 1.0, None, 0);
+        assert!(bass.voices[1].active && bass.voices[1].note == 45);
+    }
+
+    #[test]
+    fn amp_delay_starts_in_the_delay_stage() {
+        let mut bass = ArtcodeBass::default();
+        bass.note_on_voice(40, 1.0, None, 0);
+        assert_eq!(bass.voices[0].env_stage, 1);
+        let mut bass = bass_with(|p| p.amp_delay = FloatParam::new("Dly", 0.05, FloatRange::Linear { min: 0.0, max: 2.0 }));
+        bass.note_on_voice(40, 1.0, None, 0);
+        assert_eq!(bass.voices[0].env_stage, ENV_DELAY);
+    }
+
+    #[test]
+    fn lfo_divisions_in_beats() {
+        assert_eq!(lfo_division_beats(0), 4.0);
+        assert_eq!(lfo_division_beats(4), 1.0);
+        // Dotted and triplet eighths
+        assert_eq!(lfo_division_beats(8), 0.75);
+        assert!((lfo_division_beats(9) - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(lfo_division_beats(LFO_DIVISION_NAMES.len() as i32 - 1), 0.125);
+        // A quarter at 120 BPM cycles twice a second
+        assert_eq!(120.0 / 60.0 / lfo_division_beats(4), 2.0);
+    }
+
+    #[test]
+    fn lfo_start_phase_and_modes() {
+        let mut st = LfoState::new(1);
+        let sine = LfoSettings { inc: 0.125, start: 0.25, ..LfoSettings::default() };
+        assert!((st.value(&sine, &LfoShape::default()) - 1.0).abs() < 1e-6);
+
+        // S&H steps where the shifted cycle wraps, so a half-cycle start moves the step to mid-cycle
+        let steps = |start: f64| {
+            let (mut st, s) = (LfoState::new(1), LfoSettings { inc: 0.125, wave: LFO_SAMPLE_HOLD, start, ..LfoSettings::default() });
+            (0..8).map(|_| { let prev = st.rand_next; st.advance(&s); st.rand_next != prev }).collect::<Vec<_>>()
+        };
+        assert_eq!(steps(0.0).iter().position(|&s| s), Some(7));
+        assert_eq!(steps(0.5).iter().position(|&s| s), Some(3));
+
+        // Retrigger resets the cycle and draws a new step, free running only restarts the fade-in
+        st.phase = 0.6;
+        let prev = st.rand_next;
+        st.note_on(false);
+        assert_eq!((st.phase, st.fade, st.rand_next), (0.6, 0.0, prev));
+        st.note_on(true);
+        assert!(st.phase == 0.0 && st.rand_next != prev);
+
+        // One-shot stops at the end of its cycle
+        let one_shot = LfoSettings { inc: 0.5, mode: LFO_ONE_SHOT, ..LfoSettings::default() };
+        for _ in 0..4 { st.advance(&one_shot); }
+        assert_eq!(st.phase, LFO_ONE_SHOT_END);
+    }
+
+    #[test]
+    fn lfo_waves() {
+        for (wave, at_quarter) in [(0, 1.0), (1, -0.5), (2, 1.0), (3, 0.0), (4, 0.5)] {
+            assert!((gen_wave(0.25, wave) - at_quarter).abs() < 1e-6, "wave {}", wave);
+            assert!((-1.0..=1.0).contains(&gen_wave(0.6, wave)));
+        }
+        // Random shapes stay in range, smooth random glides from the previous step to the next
+        let mut st = LfoState::new(7);
+        let s = LfoSettings { inc: 0.01, wave: LFO_SMOOTH_RANDOM, fade_step: 0.25, ..LfoSettings::default() };
+        st.note_on(true);
+        st.next_random();
+        assert!((st.value(&s, &LfoShape::default()) - st.rand_prev).abs() < 1e-6);
+        for _ in 0..1000 {
+            let prev = st.value(&s, &LfoShape::default());
+            st.advance(&s);
+            let v = st.value(&s, &LfoShape::default());
+            assert!((-1.0..=1.0).contains(&st.rand_next) && (v - prev).abs() < 0.1);
+        }
+        // Fade-in ramps up over 1 / fade_step samples
+        assert_eq!(st.fade, 1.0);
+        st.note_on(false);
+        st.advance(&s);
+        assert_eq!(st.fade, 0.25);
+    }
+
+    #[test]
+    fn drawn_lfo_shape_edits() {
+        let mut shape = LfoShape::default();
+        // Default triangle: 1 at the start, -1 halfway, back up across the wrap
+        assert_eq!((shape.eval(0.0), shape.eval(0.5)), (1.0, -1.0));
+        assert!((shape.eval(0.25) - 0.0).abs() < 1e-6 && (shape.eval(0.75) - 0.0).abs() < 1e-6);
+
+        assert_eq!(shape.insert(0.25, 0.5), Some(1));
+        assert_eq!(shape.insert(0.9, 0.0), Some(3));
+        assert_eq!((shape.len, shape.eval(0.25), shape.points[2].x), (4, 0.5, 0.5));
+        shape.remove(0);
+        assert_eq!(shape.len, 4);
+        shape.remove(1);
+        assert_eq!((shape.len, shape.points[1].x), (3, 0.5));
+        while shape.insert(0.7, 0.0).is_some() {}
+        assert_eq!(shape.len, MAX_LFO_POINTS);
+
+        // Curvature bends a segment without moving its ends
+        let bent = LfoShape::from_points(&[(0.0, 0.0, 1.0), (0.5, 1.0, 0.0)]);
+        assert!(bent.eval(0.25) > 0.5 && bent.eval(0.5) == 1.0);
+
+        let mut bad = LfoShape::from_points(&[(0.4, f32::NAN, 5.0), (0.2, 3.0, 0.0)]);
+        bad.len = 0;
+        bad.sanitize();
+        assert_eq!(bad.len, 1);
+        let mut bad = LfoShape::from_points(&[(0.4, f32::NAN, 5.0), (0.2, 3.0, 0.0)]);
+        bad.sanitize();
+        assert!(bad.points[0] == LfoPoint { x: 0.0, y: 1.0, curve: 0.0 } && bad.points[1] == LfoPoint { x: 0.4, y: 0.0, curve: 1.0 });
+    }
+
+    #[test]
+    fn mod_matrix_slots_and_sources() {
+        // current_note sits an octave down, so key 0 is middle C and key 1 five octaves up
+        let global = ModSources { mod_wheel: 0.5, lfo: [0.25, 0.0, 0.0], ..ModSources::default() };
+        let v = Voice { current_note: 48.0, velocity: 0.8, env_value: 0.4, ..Voice::default() };
+        let src = v.mod_sources(&global);
+        assert_eq!((src.key, src.velocity, src.amp_env, src.mod_wheel), (0.0, 0.8, 0.4, 0.5));
+        assert_eq!(Voice { current_note: 108.0, ..Voice::default() }.mod_sources(&global).key, 1.0);
+
+        let slot = ModSlot { source: MOD_SRC_LFO1, dest: MOD_DEST_CUTOFF as i32, amount: 0.5, via: MOD_SRC_MOD_WHEEL };
+        assert!(slot.active() && !slot.is_voice());
+        assert_eq!(slot.value(&src), 0.0625);
+        assert_eq!(ModSlot { via: 0, ..slot }.value(&src), 0.125);
+        assert!(ModSlot { via: MOD_SRC_VELOCITY, ..slot }.is_voice());
+        assert!(!ModSlot { amount: 0.0, ..slot }.active());
+
+        assert!(!is_global_dest(MOD_DEST_CUTOFF) && !is_global_dest(MOD_DEST_TUNE));
+        assert!(is_global_dest(MOD_DEST_LFO1_RATE) && is_global_dest(MOD_DEST_VOLUME) && is_global_dest(MOD_DEST_DELAY_TIME));
+        // LFO phase and fade are the last destinations
+        assert!(is_global_dest(MOD_DEST_NAMES.len() - 1) && MOD_DEST_LFO1_PHASE + 6 == MOD_DEST_NAMES.len());
+    }
+
+    #[test]
+    fn bend_ranges() {
+        assert_eq!(bend_semitones(0.0, 2, 12), 0.0);
+        assert_eq!(bend_semitones(1.0, 2, 12), 2.0);
+        assert_eq!(bend_semitones(-1.0, 2, 12), -12.0);
+        assert_eq!(bend_semitones(-0.5, 2, 12), -6.0);
+        assert_eq!(bend_semitones(1.0, 0, 12), 0.0);
+    }
+
+    fn legato_glide() -> ArtcodeBass { bass_with(|p| p.glide_legato = IntParam::new("GlideLegato", 1, IntRange::Linear { min: 0, max: 1 })) }
+
+    #[test]
+    fn sustain_pedal_holds_released_keys() {
+        let mut bass = legato_glide();
+        bass.note_on_voice(40, 1.0, None, 0);
+        bass.note_on_voice(43, 1.0, None, 0);
+        // Only the picked key goes to the pedal
+        bass.sustain_voices(|v| v.note == 40);
+        assert!(bass.voices[0].sustained && !bass.voices[1].sustained && bass.voices[0].env_stage == 1);
+        bass.release_sustained();
+        assert_eq!((bass.voices[0].env_stage, bass.voices[1].env_stage), (4, 1));
+        assert!(!bass.voices[0].sustained);
+
+        // A pedal-held voice isn't a held key, so legato-only glide doesn't start from it
+        let mut bass = legato_glide();
+        bass.note_on_voice(40, 1.0, None, 0);
+        bass.sustain_voices(|v| v.note == 40);
+        bass.note_on_voice(47, 1.0, None, 0);
+        assert_eq!(bass.voices[1].current_note, bass.voices[1].target_note);
+        bass.note_on_voice(52, 1.0, None, 0);
+        assert_eq!(bass.voices[2].current_note, bass.voices[1].target_note);
+    }
+
+    #[test]
+    fn midi_cc_values_and_pending_flags() {
+        let values = MidiCcState::default();
+        assert_eq!(values.take(74), None);
+        values.store(74, 0.5);
+        assert_eq!((values.take(74), values.take(74)), (Some(0.5), None));
+
+        let map = MidiCcMap::default();
+        map.mapped[74].store(true, Ordering::Relaxed);
+        assert!(!map.mark(1));
+        // Only the first CC of a burst queues a GUI task
+        assert!(map.mark(74) && !map.mark(74));
+        // With no editor context yet the value stays pending and the next CC queues again
+        map.apply(&values, None);
+        assert!(map.pending[74].load(Ordering::Relaxed) && map.mark(74));
+        map.rebuild(&BassParams::default(), &[]);
+        assert!(!map.mark(74));
+    }
+
+    #[test]
+    fn macro_routes_add_up() {
+        let mut growl = default_macros()[0];
+        growl.value = 1.0;
+        let mut half = MacroPreset { value: 0.5, ..MacroPreset::default() };
+        half.routes[0] = MacroRoute { dest: MOD_DEST_CUTOFF as i32, amount: -0.2 };
+        let offsets = macro_offsets(&[MacroParams::new("Growl", &growl), MacroParams::new("Macro 2", &half)]);
+        assert!((offsets[MOD_DEST_CUTOFF] - 0.25).abs() < 1e-6);
+        assert_eq!((offsets[MOD_DEST_DRIVE], offsets[MOD_DEST_LFO1_RATE + 1]), (0.5, 0.6));
+        assert_eq!(offsets[MOD_DEST_RESO], 0.0);
+        // At zero a macro leaves everything alone
+        assert!(macro_offsets(&[MacroParams::new("Growl", &default_macros()[0])]).iter().all(|&o| o == 0.0));
+    }
+
+    #[test]
+    fn voice_ids_and_terminated_voices() {
+        assert_ne!(fallback_voice_id(40, 0), fallback_voice_id(40, 1));
+        assert_ne!(fallback_voice_id(40, 0), fallback_voice_id(41, 0));
+
+        // Stealing a voice reports the one it ends
+        let mut bass = ArtcodeBass::default();
+        for n in 0..MAX_VOICES as u8 { assert_eq!(bass.note_on_voice(30 + n, 1.0, Some(n as i32), 0), None); }
+        assert_eq!(bass.note_on_voice(90, 1.0, Some(99), 0), Some((0, 0, 30)));
+        assert_eq!(bass.voices[0].voice_id, 99);
+
+        // So does a legato takeover under a new voice ID, but not the same one again
+        let mut bass = retrig(RETRIG_LEGATO);
+        bass.note_on_voice(40, 1.0, Some(5), 2);
+        assert_eq!(bass.note_on_voice(43, 1.0, Some(6), 2), Some((5, 2, 40)));
+        assert_eq!(bass.note_on_voice(45, 1.0, Some(6), 2), None);
+    }
+
+    #[test]
+    fn mpe_member_channels() {
+        let mut bass = ArtcodeBass::default();
+        bass.mpe[3].bend = 0.5;
+        assert!(!bass.mpe_member(3) && bass.mpe_bend_target(3) == 0.0);
+
+        let mut bass = bass_with(|p| p.mpe_mode = IntParam::new("MPE", 1, IntRange::Linear { min: 0, max: 1 }));
+        bass.mpe[3].bend = 0.5;
+        assert!(bass.mpe_member(3) && !bass.mpe_member(MPE_MASTER_CHANNEL));
+        // Half the default 48 semitone range
+        assert_eq!(bass.mpe_bend_target(3), 24.0);
+        bass.note_on_voice(40, 1.0, None, 3);
+        bass.note_on_voice(40, 1.0, Some(7), 4);
+        assert_eq!((bass.voices[0].mpe_bend, bass.voices[1].mpe_bend), (24.0, 0.0));
+
+        // Expressions find voices by host ID, or by channel and key without one
+        assert_eq!(bass.expression_voices(Some(7), 0, 0).count(), 1);
+        assert_eq!(bass.expression_voices(None, 3, 40).map(|v| v.channel).collect::<Vec<_>>(), [3]);
+        assert_eq!(bass.expression_voices(None, 5, 40).count(), 0);
+    }
+
+    // Samples a voice takes to glide from note 0 to target at 1 kHz with a 100 ms porta time
+    fn glide_samples(glide_type: i32, target: f32) -> usize {
+        let mut v = Voice { current_note: 0.0, target_note: target, ..Voice::default() };
+        let coef = rc_stage_coef(0.001, 0.1);
+        (1..10000).find(|_| { v.glide(0.1, glide_type, coef, 1000.0); v.current_note == target }).unwrap()
+    }
+
+    #[test]
+    fn glide_rate_time_and_exp() {
+        // Rate: porta time per semitone, so a 12 semitone jump takes 12x as long
+        assert!(glide_samples(0, 1.0).abs_diff(100) <= 1 && glide_samples(0, 12.0).abs_diff(1200) <= 1);
+        // Time: the whole glide takes the porta time whatever the interval
+        assert!(glide_samples(GLIDE_TIME, 1.0).abs_diff(100) <= 1 && glide_samples(GLIDE_TIME, 12.0).abs_diff(100) <= 1);
+        // Exp: about 1% left after the porta time, then it snaps once within 0.01
+        assert!(glide_samples(GLIDE_EXP, 1.0).abs_diff(100) <= 2);
+
+        // The constant-time step is reset on arrival, so the next glide works out its own
+        let mut v = Voice { current_note: 0.0, target_note: 12.0, ..Voice::default() };
+        while v.current_note != v.target_note { v.glide(0.1, GLIDE_TIME, 0.0, 1000.0); }
+        assert_eq!(v.glide_step, 0.0);
+        v.target_note = 13.0;
+        v.glide(0.1, GLIDE_TIME, 0.0, 1000.0);
+        assert!((v.glide_step - 0.01).abs() < 1e-6);
+
+        let mut v = Voice { current_note: 0.0, target_note: 12.0, ..Voice::default() };
+        v.glide(0.0, 0, 0.0, 1000.0);
+        assert_eq!(v.current_note, 12.0);
+    }
+}